@@ -0,0 +1,58 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs::Metadata;
+use std::time::SystemTime;
+use std::{error, io};
+
+use chksum::Error;
+
+use crate::context::source;
+
+/// State of file which changes whenever file is modified.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Snapshot {
+    length: u64,
+    modified: Option<SystemTime>,
+    /// Time of last status change in seconds and nanoseconds.
+    #[cfg(unix)]
+    changed: (i64, i64),
+}
+
+impl Snapshot {
+    /// Takes snapshot of file state from metadata.
+    pub fn new(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            length: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            changed: (metadata.ctime(), metadata.ctime_nsec()),
+        }
+    }
+}
+
+/// Error of file which has been modified while being hashed.
+#[derive(Debug)]
+pub(crate) struct ChangedError;
+
+impl Display for ChangedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "file changed during read")
+    }
+}
+
+impl error::Error for ChangedError {}
+
+/// Creates error of file which has been modified while being hashed.
+pub(crate) fn changed_error() -> Error {
+    io::Error::other(ChangedError).into()
+}
+
+/// Checks whether error is caused by file modified while being hashed.
+pub(crate) fn is_changed(error: &Error) -> bool {
+    match error {
+        Error::Io(error) => source(error).get_ref().is_some_and(|error| error.is::<ChangedError>()),
+        _ => false,
+    }
+}
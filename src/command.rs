@@ -0,0 +1,48 @@
+use std::fmt::{self, Display, Formatter};
+use std::process::ExitStatus;
+use std::{error, io};
+
+use chksum::Error;
+
+use crate::context::source;
+
+/// Error of command whose output is hashed.
+#[derive(Debug)]
+pub(crate) enum CommandError {
+    /// Command cannot be run, e.g. program doesn't exist.
+    Spawn(io::Error),
+    /// Command has exited unsuccessfully.
+    Failed(ExitStatus),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spawn(error) => write!(f, "cannot run command: {error}"),
+            Self::Failed(status) => write!(f, "command failed with {status}"),
+        }
+    }
+}
+
+impl error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Spawn(error) => Some(error),
+            Self::Failed(_) => None,
+        }
+    }
+}
+
+/// Creates error of command whose output is hashed.
+pub(crate) fn command_error(error: CommandError) -> Error {
+    // kind of spawn error is dropped, missing program isn't a missing input
+    io::Error::other(error).into()
+}
+
+/// Checks whether error is caused by command which cannot be run or has exited unsuccessfully.
+pub(crate) fn is_command_failed(error: &Error) -> bool {
+    match error {
+        Error::Io(error) => source(error).get_ref().is_some_and(|error| error.is::<CommandError>()),
+        _ => false,
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::{error, io};
+
+use chksum::Error;
+
+use crate::escape::escape;
+
+/// Operation on file system which has failed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Operation {
+    Open,
+    Read,
+    ReadDir,
+    Stat,
+}
+
+impl Operation {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Read => "read",
+            Self::ReadDir => "readdir",
+            Self::Stat => "stat",
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// I/O error with path and operation which has failed.
+#[derive(Debug)]
+pub(crate) struct PathError {
+    pub path: PathBuf,
+    pub operation: Operation,
+    pub source: io::Error,
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", escape(&self.path), self.operation, self.source)
+    }
+}
+
+impl error::Error for PathError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Extension which attaches path and operation to I/O errors.
+pub(crate) trait Context {
+    /// Wraps I/O error with path and operation, keeping its kind.
+    #[must_use]
+    fn context(self, path: &Path, operation: Operation) -> Self;
+}
+
+impl<T> Context for io::Result<T> {
+    fn context(self, path: &Path, operation: Operation) -> Self {
+        self.map_err(|source| attach(source, path, operation))
+    }
+}
+
+impl<T> Context for Result<T, Error> {
+    fn context(self, path: &Path, operation: Operation) -> Self {
+        self.map_err(|error| {
+            match error {
+                Error::Io(source) => Error::Io(attach(source, path, operation)),
+                error => error,
+            }
+        })
+    }
+}
+
+/// Wraps I/O error with path and operation unless it already has them.
+fn attach(source: io::Error, path: &Path, operation: Operation) -> io::Error {
+    // innermost path is the most precise one
+    if path_error(&source).is_some() {
+        return source;
+    }
+    let kind = source.kind();
+    let path = path.to_path_buf();
+    io::Error::new(
+        kind,
+        PathError {
+            path,
+            operation,
+            source,
+        },
+    )
+}
+
+/// Returns path and operation attached to error.
+pub(crate) fn path_error(error: &io::Error) -> Option<&PathError> {
+    error.get_ref().and_then(|error| error.downcast_ref())
+}
+
+/// Returns I/O error without attached path and operation.
+pub(crate) fn source(error: &io::Error) -> &io::Error {
+    path_error(error).map_or(error, |error| &error.source)
+}
+
+/// Removes path and operation from error when path is the input itself.
+pub(crate) fn strip(error: Error, path: &Path) -> Error {
+    match error {
+        Error::Io(error) if path_error(&error).is_some_and(|error| error.path == path) => {
+            let error = error.into_inner().and_then(|error| error.downcast::<PathError>().ok());
+            let error = error.expect("Error has path attached");
+            Error::Io(error.source)
+        },
+        error => error,
+    }
+}
+
+/// Describes error for humans, paths are kept intact while messages are lowercased.
+pub(crate) fn describe(error: &Error) -> String {
+    match error {
+        Error::Io(error) => {
+            match path_error(error) {
+                Some(PathError {
+                    path,
+                    operation,
+                    source,
+                }) => {
+                    let source = source.to_string().to_lowercase();
+                    format!("{}: {operation}: {source}", escape(path))
+                },
+                None => error.to_string().to_lowercase(),
+            }
+        },
+        error => error.to_string().to_lowercase(),
+    }
+}
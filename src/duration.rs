@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// Parses duration given in seconds with optional unit, e.g. `2`, `250ms`, `1.5s` or `1m`.
+pub(crate) fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let index = input
+        .find(|char: char| !char.is_ascii_digit() && char != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(index);
+    let number: f64 = number.parse().map_err(|_| format!("invalid duration `{input}`"))?;
+    let multiplier = match unit.trim() {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 60.0 * 60.0,
+        unit => return Err(format!("unknown duration unit `{unit}`")),
+    };
+    Duration::try_from_secs_f64(number * multiplier).map_err(|_| format!("duration `{input}` is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("1m"), Ok(Duration::from_secs(60)));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use std::borrow::Cow;
+#[cfg(unix)]
+use std::fmt::Write;
+use std::path::Path;
+#[cfg(unix)]
+use std::str;
+
+/// Escapes path so it can be printed losslessly in a newline-delimited record.
+///
+/// Backslashes are doubled, newlines become `\n` and bytes which are not valid UTF-8 become `\xHH`.
+#[cfg(unix)]
+pub(crate) fn escape(path: &Path) -> Cow<'_, str> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut bytes = path.as_os_str().as_bytes();
+    if let Ok(path) = str::from_utf8(bytes) {
+        if !path.contains(['\\', '\n']) {
+            return Cow::Borrowed(path);
+        }
+    }
+
+    let mut escaped = String::with_capacity(bytes.len());
+    loop {
+        match str::from_utf8(bytes) {
+            Ok(valid) => {
+                escape_str(&mut escaped, valid);
+                break;
+            },
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                let valid = str::from_utf8(valid).expect("Prefix must be valid UTF-8");
+                escape_str(&mut escaped, valid);
+                let length = error.error_len().unwrap_or(rest.len());
+                let (invalid, rest) = rest.split_at(length);
+                for byte in invalid {
+                    write!(escaped, "\\x{byte:02x}").expect("Cannot write to string");
+                }
+                bytes = rest;
+            },
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Escapes path so it can be printed losslessly in a newline-delimited record.
+#[cfg(not(unix))]
+pub(crate) fn escape(path: &Path) -> Cow<'_, str> {
+    path.to_string_lossy()
+}
+
+/// Escapes valid UTF-8 part of path.
+#[cfg(unix)]
+fn escape_str(escaped: &mut String, path: &str) {
+    for char in path.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            char => escaped.push(char),
+        }
+    }
+}
+
+/// Reverses [`escape`].
+///
+/// Unknown escape sequences are left untouched.
+#[cfg(unix)]
+pub(crate) fn unescape(bytes: &[u8]) -> Cow<'_, [u8]> {
+    if !bytes.contains(&b'\\') {
+        return Cow::Borrowed(bytes);
+    }
+
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match &bytes[index..] {
+            [b'\\', b'\\', ..] => {
+                unescaped.push(b'\\');
+                index += 2;
+            },
+            [b'\\', b'n', ..] => {
+                unescaped.push(b'\n');
+                index += 2;
+            },
+            [b'\\', b'x', high, low, ..] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                let hex = [*high, *low];
+                let hex = str::from_utf8(&hex).expect("Hex digits must be valid UTF-8");
+                let byte = u8::from_str_radix(hex, 16).expect("Hex digits must be valid number");
+                unescaped.push(byte);
+                index += 4;
+            },
+            [byte, ..] => {
+                unescaped.push(*byte);
+                index += 1;
+            },
+            [] => unreachable!(),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// Reverses [`escape`].
+#[cfg(not(unix))]
+pub(crate) fn unescape(bytes: &[u8]) -> Cow<'_, [u8]> {
+    Cow::Borrowed(bytes)
+}
+
+/// Returns raw bytes of path, used for NUL-delimited records.
+#[cfg(unix)]
+pub(crate) fn raw(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+/// Returns raw bytes of path, used for NUL-delimited records.
+#[cfg(not(unix))]
+pub(crate) fn raw(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(path) => Cow::Borrowed(path.as_bytes()),
+        Cow::Owned(path) => Cow::Owned(path.into_bytes()),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    use super::*;
+
+    #[test]
+    fn escape_utf8() {
+        assert_eq!(escape(Path::new("dir/file")), "dir/file");
+        assert_eq!(escape(Path::new("back\\slash")), "back\\\\slash");
+        assert_eq!(escape(Path::new("new\nline")), "new\\nline");
+    }
+
+    #[test]
+    fn escape_non_utf8() {
+        let path = Path::new(OsStr::from_bytes(b"file\xFF\xFE.txt"));
+        assert_eq!(escape(path), "file\\xff\\xfe.txt");
+    }
+
+    #[test]
+    fn round_trip() {
+        let paths: [&[u8]; 4] = [b"plain", b"a\\xff", b"\xC3\x28\\\n", b"\\x4"];
+        for path in paths {
+            let path = Path::new(OsStr::from_bytes(path));
+            let escaped = escape(path);
+            assert_eq!(unescape(escaped.as_bytes()), path.as_os_str().as_bytes());
+        }
+    }
+}
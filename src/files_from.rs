@@ -0,0 +1,91 @@
+use std::fs::File;
+use std::io::{self, stdin, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::escape::unescape;
+use crate::Input;
+
+/// Streams paths listed in a file or in stdin.
+pub(crate) struct FilesFrom {
+    input: Input,
+    reader: Option<Box<dyn BufRead + Send>>,
+    delimiter: u8,
+}
+
+impl FilesFrom {
+    /// Opens a list of paths, `-` stands for stdin.
+    pub fn open(path: &Path, null: bool) -> Result<Self, (Input, io::Error)> {
+        let (input, reader): (Input, Box<dyn BufRead + Send>) = if path == Path::new("-") {
+            (Input::Stdin(None), Box::new(BufReader::new(stdin())))
+        } else {
+            let input = Input::from(path);
+            match File::open(path) {
+                Ok(file) => (input, Box::new(BufReader::new(file))),
+                Err(error) => return Err((input, error)),
+            }
+        };
+        let delimiter = if null { b'\0' } else { b'\n' };
+        let files_from = Self {
+            input,
+            reader: Some(reader),
+            delimiter,
+        };
+        Ok(files_from)
+    }
+}
+
+impl Iterator for FilesFrom {
+    type Item = Result<PathBuf, (Input, io::Error)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let reader = self.reader.as_mut()?;
+        loop {
+            let mut buffer = Vec::new();
+            match reader.read_until(self.delimiter, &mut buffer) {
+                Ok(0) => {
+                    self.reader = None;
+                    return None;
+                },
+                Ok(_) => {
+                    if buffer.last() == Some(&self.delimiter) {
+                        buffer.pop();
+                    }
+                    // empty records are skipped, e.g. trailing delimiter
+                    if buffer.is_empty() {
+                        continue;
+                    }
+                    // newline-delimited records starting with a backslash use the same escaping as the output
+                    if self.delimiter == b'\n' && buffer.first() == Some(&b'\\') {
+                        buffer = unescape(&buffer[1..]).into_owned();
+                    }
+                    let path = path_from_bytes(buffer).map_err(|error| (self.input.clone(), error));
+                    return Some(path);
+                },
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    // stop reading the list after the first error
+                    self.reader = None;
+                    return Some(Err((self.input.clone(), error)));
+                },
+            }
+        }
+    }
+}
+
+/// Turns raw bytes into path.
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> io::Result<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    let path = OsString::from_vec(bytes).into();
+    Ok(path)
+}
+
+/// Turns raw bytes into path.
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> io::Result<PathBuf> {
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
@@ -0,0 +1,204 @@
+use std::fs::Metadata;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+use crate::Options;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntryType {
+    File,
+    Symlink,
+}
+
+impl EntryType {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "f",
+            Self::Symlink => "l",
+        }
+    }
+}
+
+impl ValueEnum for EntryType {
+    fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
+        let input = if ignore_case {
+            input.to_lowercase()
+        } else {
+            input.to_string()
+        };
+        match &input[..] {
+            "f" => Ok(Self::File),
+            "l" => Ok(Self::Symlink),
+            _ => Err("unrecognized option".to_string()),
+        }
+    }
+
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::File, Self::Symlink]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let possible_value = PossibleValue::new(self.as_str());
+        Some(possible_value)
+    }
+}
+
+/// Filters of files found in directories.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Filter {
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    newer: Option<SystemTime>,
+    older: Option<SystemTime>,
+    files: bool,
+    symlinks: bool,
+}
+
+impl Filter {
+    /// Creates filter from options.
+    pub fn new(options: &Options) -> Self {
+        Self {
+            min_size: options.min_size,
+            max_size: options.max_size,
+            newer: options.newer,
+            older: options.older,
+            files: options.types.contains(&EntryType::File),
+            symlinks: options.types.contains(&EntryType::Symlink),
+        }
+    }
+
+    /// Checks whether file found in directory should be hashed.
+    ///
+    /// Metadata must describe target of symbolic link, directories always match.
+    pub fn matches(&self, path: &Path, metadata: &Metadata) -> io::Result<bool> {
+        if metadata.is_dir() {
+            return Ok(true);
+        }
+        if self.files || self.symlinks {
+            let is_symlink = path.symlink_metadata()?.file_type().is_symlink();
+            let matches = if is_symlink {
+                self.symlinks
+            } else {
+                self.files && metadata.is_file()
+            };
+            if !matches {
+                return Ok(false);
+            }
+        }
+        let length = metadata.len();
+        if self.min_size.is_some_and(|min_size| length < min_size) {
+            return Ok(false);
+        }
+        if self.max_size.is_some_and(|max_size| length > max_size) {
+            return Ok(false);
+        }
+        if self.newer.is_some() || self.older.is_some() {
+            let modified = metadata.modified()?;
+            if self.newer.is_some_and(|newer| modified <= newer) {
+                return Ok(false);
+            }
+            if self.older.is_some_and(|older| modified >= older) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Parses time given as modification time of existing file, `@` followed by Unix timestamp, age like `7d` (units
+/// `s`, `m`, `h`, `d` and `w`) or UTC date like `2024-01-31` or `2024-01-31 12:00[:00]`.
+pub(crate) fn parse_time(input: &str) -> Result<SystemTime, String> {
+    let path = Path::new(input);
+    if path.exists() {
+        return path
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .map_err(|error| format!("cannot read modification time of `{input}`: {error}"));
+    }
+    let invalid = || format!("invalid file or time `{input}`");
+    if let Some(timestamp) = input.strip_prefix('@') {
+        let timestamp: u64 = timestamp.parse().map_err(|_| invalid())?;
+        return Ok(UNIX_EPOCH + Duration::from_secs(timestamp));
+    }
+    if let Some(unit) = input.chars().last().filter(char::is_ascii_alphabetic) {
+        let number: u64 = input[..input.len() - 1].parse().map_err(|_| invalid())?;
+        let seconds = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let age = number
+            .checked_mul(seconds)
+            .map(Duration::from_secs)
+            .ok_or_else(invalid)?;
+        return SystemTime::now().checked_sub(age).ok_or_else(invalid);
+    }
+    let (date, time) = input.split_once([' ', 'T']).unwrap_or((input, "00:00"));
+    let date: Vec<&str> = date.split('-').collect();
+    let time: Vec<&str> = time.split(':').collect();
+    let (year, month, day) = match date[..] {
+        [year, month, day] => (year, month, day),
+        _ => return Err(invalid()),
+    };
+    let (hour, minute, second) = match time[..] {
+        [hour, minute] => (hour, minute, "0"),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return Err(invalid()),
+    };
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let [month, day, hour, minute, second] = [month, day, hour, minute, second].map(|value| value.parse::<u64>().ok());
+    let (month, day, hour, minute, second) = match (month, day, hour, minute, second) {
+        (
+            Some(month @ 1..=12),
+            Some(day @ 1..=31),
+            Some(hour @ 0..=23),
+            Some(minute @ 0..=59),
+            Some(second @ 0..=59),
+        ) => (month, day, hour, minute, second),
+        _ => return Err(invalid()),
+    };
+    let days = days_from_civil(year, month, day);
+    let seconds = u64::try_from(days).map_err(|_| invalid())? * 24 * 60 * 60 + hour * 60 * 60 + minute * 60 + second;
+    Ok(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Returns number of days since Unix epoch of given date in proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u64, day: u64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time_formats() {
+        assert_eq!(parse_time("@86400"), Ok(UNIX_EPOCH + Duration::from_secs(86_400)));
+        assert_eq!(parse_time("1970-01-02"), Ok(UNIX_EPOCH + Duration::from_secs(86_400)));
+        assert_eq!(
+            parse_time("2024-02-29 12:30"),
+            Ok(UNIX_EPOCH + Duration::from_secs(1_709_209_800))
+        );
+        assert_eq!(
+            parse_time("2024-02-29T12:30:15"),
+            Ok(UNIX_EPOCH + Duration::from_secs(1_709_209_815))
+        );
+        assert!(parse_time("7d").is_ok());
+        assert!(parse_time("7x").is_err());
+        assert!(parse_time("2024-13-01").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Raw bytes given as hexadecimal string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Hex(Vec<u8>);
+
+impl Hex {
+    /// Returns decoded bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Display for Hex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// Parses hexadecimal string with optional `0x` prefix, whitespace between digits is ignored.
+pub(crate) fn parse_hex(input: &str) -> Result<Hex, String> {
+    let trimmed = input.trim();
+    let trimmed = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let digits = trimmed
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .map(|char| char.to_digit(16).ok_or_else(|| format!("invalid hex digit `{char}`")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if digits.len() % 2 != 0 {
+        return Err(format!("odd number of hex digits in `{input}`"));
+    }
+    let bytes = digits.chunks(2).map(|pair| (pair[0] * 16 + pair[1]) as u8).collect();
+    Ok(Hex(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_formats() {
+        assert_eq!(parse_hex("61646d696e31"), Ok(Hex(b"admin1".to_vec())));
+        assert_eq!(parse_hex("0xDEAD beef"), Ok(Hex(vec![0xDE, 0xAD, 0xBE, 0xEF])));
+        assert_eq!(parse_hex(""), Ok(Hex(Vec::new())));
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("zz").is_err());
+        assert_eq!(parse_hex("0x00FF").map(|hex| hex.to_string()), Ok("00ff".to_string()));
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::{self, Read};
+#[cfg(target_os = "linux")]
+use std::os::fd::AsFd;
+
+#[cfg(target_os = "linux")]
+use rustix::event::{poll, PollFd, PollFlags};
+
+#[cfg(target_os = "linux")]
+use crate::progress::INTERVAL;
+use crate::signal;
+
+/// Reader of pipes and terminals which gives up waiting for data once processing is stopped.
+///
+/// Signal handlers restart interrupted reads, so readiness is polled with timeout instead of blocking in read.
+pub(crate) struct InterruptibleReader<R> {
+    inner: R,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    poll: bool,
+}
+
+impl<R> InterruptibleReader<R> {
+    /// Creates reader which polls `inner` before each read when `poll` is set, e.g. for anything but regular files.
+    pub fn new(inner: R, poll: bool) -> Self {
+        Self { inner, poll }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<R> InterruptibleReader<R>
+where
+    R: AsFd,
+{
+    /// Waits until inner reader has data, hangup or error to report.
+    fn wait(&self) -> io::Result<()> {
+        loop {
+            if signal::stopped() {
+                return Err(signal::stopped_error());
+            }
+            let mut fds = [PollFd::new(&self.inner, PollFlags::IN)];
+            match poll(&mut fds, INTERVAL.as_millis() as i32) {
+                Ok(0) | Err(rustix::io::Errno::INTR) => continue,
+                Ok(_) => return Ok(()),
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<R> Read for InterruptibleReader<R>
+where
+    R: Read + AsFd,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.poll {
+            self.wait()?;
+        }
+        self.inner.read(buffer)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl<R> Read for InterruptibleReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if signal::stopped() {
+            return Err(signal::stopped_error());
+        }
+        self.inner.read(buffer)
+    }
+}
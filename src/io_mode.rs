@@ -0,0 +1,173 @@
+use std::fs::File;
+#[cfg(target_os = "linux")]
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(target_os = "linux")]
+use std::os::fd::{AsFd, BorrowedFd};
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+#[cfg(target_os = "linux")]
+use rustix::fs::{fadvise, Advice, OFlags};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IoMode {
+    Normal,
+    NoCache,
+    Direct,
+}
+
+impl IoMode {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::NoCache => "nocache",
+            Self::Direct => "direct",
+        }
+    }
+
+    /// Opens file for reading.
+    ///
+    /// Mode `direct` falls back to `nocache` when file system doesn't support `O_DIRECT`.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn open(self, path: &Path) -> io::Result<IoReader> {
+        let (file, mode) = match self {
+            Self::Normal => (File::open(path)?, self),
+            Self::NoCache => (open(path, OFlags::empty())?, self),
+            Self::Direct => {
+                match open(path, OFlags::DIRECT) {
+                    Ok(file) => (file, self),
+                    Err(error) if error.kind() == io::ErrorKind::InvalidInput => {
+                        (open(path, OFlags::empty())?, Self::NoCache)
+                    },
+                    Err(error) => return Err(error),
+                }
+            },
+        };
+        if mode != Self::Normal {
+            // sequential hint only makes readahead more aggressive, failure is not an error
+            let _ = fadvise(&file, 0, 0, Advice::Sequential);
+        }
+        let reader = IoReader {
+            file,
+            mode,
+            position: 0,
+        };
+        Ok(reader)
+    }
+
+    /// Opens file for reading.
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn open(self, path: &Path) -> io::Result<IoReader> {
+        let reader = IoReader {
+            file: File::open(path)?,
+            mode: self,
+            position: 0,
+        };
+        Ok(reader)
+    }
+}
+
+impl ValueEnum for IoMode {
+    fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
+        let input = if ignore_case {
+            input.to_lowercase()
+        } else {
+            input.to_string()
+        };
+        match &input[..] {
+            "normal" => Ok(Self::Normal),
+            "nocache" => Ok(Self::NoCache),
+            "direct" => Ok(Self::Direct),
+            _ => Err("unrecognized option".to_string()),
+        }
+    }
+
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Normal, Self::NoCache, Self::Direct]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let possible_value = PossibleValue::new(self.as_str());
+        Some(possible_value)
+    }
+}
+
+/// File reader which follows [`IoMode`].
+pub(crate) struct IoReader {
+    file: File,
+    mode: IoMode,
+    position: u64,
+}
+
+impl IoReader {
+    /// Returns underlying file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Returns mode which is effectively used.
+    pub fn mode(&self) -> IoMode {
+        self.mode
+    }
+
+    /// Drops cached pages of file which has been consumed without reading, e.g. mapped.
+    #[cfg(feature = "mmap")]
+    pub fn consumed(&self) {
+        #[cfg(target_os = "linux")]
+        if self.mode != IoMode::Normal {
+            let _ = fadvise(&self.file, 0, 0, Advice::DontNeed);
+        }
+    }
+}
+
+impl Read for IoReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let length = self.file.read(buffer)?;
+        #[cfg(target_os = "linux")]
+        if self.mode == IoMode::NoCache && length > 0 {
+            // drop consumed pages so the page cache keeps its working set
+            let _ = fadvise(&self.file, self.position, length as u64, Advice::DontNeed);
+        }
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl AsFd for IoReader {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+impl Seek for IoReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = self.file.seek(position)?;
+        Ok(self.position)
+    }
+}
+
+/// Opens file with `O_NOATIME` when permitted.
+#[cfg(target_os = "linux")]
+fn open(path: &Path, flags: OFlags) -> io::Result<File> {
+    let noatime = flags | OFlags::NOATIME;
+    match OpenOptions::new()
+        .read(true)
+        .custom_flags(noatime.bits() as i32)
+        .open(path)
+    {
+        // O_NOATIME is allowed only for owner of the file
+        Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+            OpenOptions::new()
+                .read(true)
+                .custom_flags(flags.bits() as i32)
+                .open(path)
+        },
+        result => result,
+    }
+}
@@ -1,9 +1,27 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 
+mod changed;
 #[cfg(feature = "color")]
 mod color;
+mod command;
+mod context;
+mod duration;
+mod escape;
+mod files_from;
+mod filter;
+mod hex;
+mod interruptible;
+mod io_mode;
 #[cfg(feature = "md5")]
 mod md5;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod pipeline;
+mod progress;
+mod range;
+mod read;
+mod sanitize;
 #[cfg(feature = "sha1")]
 mod sha1;
 #[cfg(feature = "sha2-224")]
@@ -14,33 +32,94 @@ mod sha2_256;
 mod sha2_384;
 #[cfg(feature = "sha2-512")]
 mod sha2_512;
+mod signal;
+mod size;
+#[cfg(target_os = "linux")]
+mod sparse;
+mod special;
+mod status;
 
+use std::borrow::Cow;
+use std::ffi::OsString;
 use std::fmt::{self, Display, Formatter};
-use std::io::{self, stderr, stdin, stdout, Write};
+use std::io::{self, stderr, stdout, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use chksum::{chksum, Digest, Error, Hash};
+use chksum::{Digest, Error, Hash};
 #[cfg(feature = "color")]
 use colored::Colorize;
-use exitcode::{IOERR as EXITCODE_IOERR, OK as EXITCODE_OK};
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{ParallelBridge, ParallelIterator};
 
 #[cfg(feature = "color")]
 pub use crate::color::Color;
+use crate::context::describe;
+use crate::duration::parse_duration;
+use crate::escape::{escape, raw};
+use crate::files_from::FilesFrom;
+use crate::filter::{parse_time, EntryType, Filter};
+use crate::hex::{parse_hex, Hex};
+#[cfg(target_os = "linux")]
+use crate::io_mode::IoMode;
+#[cfg(feature = "mmap")]
+use crate::mmap::Mmap;
+use crate::progress::Progress;
+use crate::range::ByteRange;
+use crate::read::{Checksum, Event, Reader};
+use crate::sanitize::{sanitize, Sanitize};
+use crate::size::{parse_buffer_size, parse_positive_size, parse_size};
+use crate::special::SpecialFiles;
+use crate::status::{ExitCodes, Outcome, Status};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum Input {
     Path(PathBuf),
-    Stdin,
+    Range(PathBuf, ByteRange),
+    /// Stdin with optional name printed instead of `<stdin>`.
+    Stdin(Option<String>),
+    /// Literal string.
+    String(String),
+    /// Raw bytes given as hexadecimal string.
+    Hex(Hex),
+    /// Stdout of command with its arguments, command inherits stdin when flag is set.
+    Command(Vec<OsString>, bool),
+}
+
+impl Input {
+    /// Creates input of path or its byte range.
+    fn new(path: PathBuf, range: Option<ByteRange>) -> Self {
+        match range {
+            Some(range) => Self::Range(path, range),
+            None => Self::Path(path),
+        }
+    }
+
+    /// Returns path of file or directory input.
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Path(path) | Self::Range(path, _) => Some(path),
+            Self::Stdin(_) | Self::String(_) | Self::Hex(_) | Self::Command(..) => None,
+        }
+    }
 }
 
 impl Display for Input {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Path(path) => write!(f, "{}", path.display()),
-            Self::Stdin => write!(f, "<stdin>"),
+            Self::Path(path) => write!(f, "{}", escape(path)),
+            Self::Range(path, range) => write!(f, "{}{range}", escape(path)),
+            Self::Stdin(Some(name)) => write!(f, "{name}"),
+            Self::Stdin(None) => write!(f, "<stdin>"),
+            // quotes tell literals apart from paths
+            Self::String(text) => write!(f, "{text:?}"),
+            Self::Hex(hex) => write!(f, "hex:\"{hex}\""),
+            Self::Command(command, _) => {
+                let command: Vec<_> = command.iter().map(|arg| arg.to_string_lossy()).collect();
+                write!(f, "exec:{:?}", command.join(" "))
+            },
         }
     }
 }
@@ -96,34 +175,499 @@ pub enum Subcommand {
 
 #[derive(Debug, clap::Args)]
 pub(crate) struct Args {
-    /// Path to file or directory.
-    #[arg(required = true, value_name = "PATH", conflicts_with = "stdin")]
+    /// Path to file or directory (use - for stdin).
+    #[arg(required_unless_present_any = ["stdin", "files_from", "strings", "hex_inputs", "command"], value_name = "PATH")]
     pub paths: Vec<PathBuf>,
 }
 
 #[derive(Debug, clap::Args)]
 pub(crate) struct Options {
-    /// Calculate digest from stdin.
-    #[arg(short, long, default_value_t = false, conflicts_with = "paths")]
+    /// Calculate digest from stdin, same as - given as path.
+    #[arg(short, long, default_value_t = false)]
     pub stdin: bool,
+    /// Name of stdin printed instead of `<stdin>`.
+    #[arg(long, value_name = "LABEL")]
+    pub stdin_name: Option<String>,
+    /// Calculate digest of given string.
+    #[arg(long = "string", value_name = "TEXT")]
+    pub strings: Vec<String>,
+    /// Calculate digest of raw bytes given as hexadecimal string.
+    #[arg(long = "hex-input", value_name = "HEX", value_parser = parse_hex)]
+    pub hex_inputs: Vec<Hex>,
+    /// Calculate digest of output of command, its arguments end at --.
+    #[arg(long = "exec", value_name = "COMMAND", action = clap::ArgAction::Set, num_args = 1.., value_terminator = "--", allow_hyphen_values = true, value_parser = clap::value_parser!(OsString))]
+    pub command: Vec<OsString>,
+    /// Read paths from file, one per line (use - for stdin).
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+    /// Paths read with --files-from are separated by NUL instead of newline.
+    #[arg(short = '0', long, default_value_t = false, requires = "files_from")]
+    pub null: bool,
+    /// End each record on stdout with NUL instead of newline.
+    #[arg(short, long, default_value_t = false)]
+    pub zero: bool,
+    /// Escape control characters in printed paths and errors.
+    #[arg(value_enum, long, value_name = "WHEN", default_value_t = Sanitize::Auto)]
+    pub sanitize: Sanitize,
+    /// Use memory-mapped I/O for regular files, truncating mapped file during read crashes the process.
+    #[arg(value_enum, long, value_name = "WHEN", default_value_t = Mmap::Never)]
+    #[cfg(feature = "mmap")]
+    pub mmap: Mmap,
+    /// Smallest file memory-mapped with --mmap auto.
+    #[arg(long, value_name = "SIZE", default_value = "64MiB", value_parser = parse_size)]
+    #[cfg(feature = "mmap")]
+    pub mmap_threshold: u64,
+    /// The way files are read, nocache drops read pages from the page cache and skips atime updates, direct
+    /// bypasses the page cache.
+    #[arg(value_enum, long, value_name = "MODE", default_value_t = IoMode::Normal)]
+    #[cfg(target_os = "linux")]
+    pub io_mode: IoMode,
+    /// Read on a separate thread, so reading and hashing of single file or stdin overlap.
+    #[arg(long, default_value_t = false)]
+    pub pipeline: bool,
+    /// Size of single read, rounded up to multiple of 4KiB for direct I/O.
+    #[arg(long, value_name = "SIZE", default_value = "256KiB", value_parser = parse_buffer_size)]
+    pub buffer_size: usize,
+    /// Hash file starting from given byte.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, conflicts_with = "stdin")]
+    pub offset: Option<u64>,
+    /// Hash at most given number of bytes of file.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, conflicts_with = "stdin")]
+    pub length: Option<u64>,
+    /// Skip holes of sparse files, holes are hashed as zeros without reading them.
+    #[arg(long, default_value_t = false)]
+    #[cfg(target_os = "linux")]
+    pub sparse: bool,
+    /// Handling of FIFOs, sockets and device nodes found in directories.
+    #[arg(value_enum, long, value_name = "POLICY", default_value_t = SpecialFiles::Skip)]
+    pub special_files: SpecialFiles,
+    /// Hash only files of at least given size found in directories.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub min_size: Option<u64>,
+    /// Hash only files of at most given size found in directories.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_size: Option<u64>,
+    /// Hash only files found in directories modified after given file, `@TIMESTAMP`, age like `7d` or UTC date.
+    #[arg(long, value_name = "FILE|TIME", value_parser = parse_time)]
+    pub newer: Option<SystemTime>,
+    /// Hash only files found in directories modified before given file, `@TIMESTAMP`, age like `7d` or UTC date.
+    #[arg(long, value_name = "FILE|TIME", value_parser = parse_time)]
+    pub older: Option<SystemTime>,
+    /// Hash only regular files (f) or symbolic links (l) found in directories.
+    #[arg(value_enum, long = "type", value_name = "TYPE")]
+    pub types: Vec<EntryType>,
+    /// Hash input again up to given number of times when its file changes during read.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retry_changed: u32,
+    /// Hash input again up to given number of times after transient I/O error, e.g. `EIO` or `ESTALE`.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retries: u32,
+    /// Delay before first retry after transient I/O error, doubled with every next retry.
+    #[arg(long, value_name = "DURATION", default_value = "100ms", value_parser = parse_duration)]
+    pub retry_delay: Duration,
+    /// Skip entries of directories which cannot be opened, listed or stat'ed, digest is then partial.
+    #[arg(long, default_value_t = false)]
+    pub skip_unreadable: bool,
+    /// Exit code of multiple inputs: code of the first failure, of the most severe one, or bitmask of all of them.
+    #[arg(value_enum, long, value_name = "POLICY", default_value_t = ExitCodes::First)]
+    pub exit_codes: ExitCodes,
+    /// Stop processing inputs after the first error.
+    #[arg(long, default_value_t = false, conflicts_with = "max_errors")]
+    pub fail_fast: bool,
+    /// Stop processing inputs after given number of errors.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_errors: Option<u64>,
+    /// Also print digest of each block of given size.
+    #[arg(long, value_name = "SIZE", value_parser = parse_positive_size)]
+    pub block_size: Option<u64>,
+    /// Print only errors.
+    #[arg(short, long, default_value_t = false, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Print nothing, exit code reports the result.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["quiet", "verbose", "summary"])]
+    pub status: bool,
+    /// Print number of processed inputs, bytes read, elapsed time and throughput to stderr at the end.
+    #[arg(long, default_value_t = false)]
+    pub summary: bool,
+    /// Show progress of hashing on stderr when it is a terminal.
+    #[arg(long, default_value_t = false)]
+    pub progress: bool,
+    /// Sum sizes of all inputs first, so progress shows percentage and ETA.
+    #[arg(long, default_value_t = false, requires = "progress")]
+    pub prescan: bool,
+    /// Print details of processing to stderr, twice for details of directory walks.
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+}
+
+/// Output settings used by the printer thread.
+#[derive(Clone, Copy, Debug)]
+struct Format {
+    /// Records end with NUL instead of newline.
+    zero: bool,
+    /// Control characters printed to stdout are escaped.
+    sanitize_stdout: bool,
+    /// Control characters printed to stderr are escaped.
+    sanitize_stderr: bool,
+    /// Size of blocks which digests are printed.
+    block_size: Option<u64>,
+    /// Only errors are printed.
+    quiet: bool,
+    /// Nothing is printed.
+    status: bool,
+    /// Level of details printed to stderr.
+    verbose: u8,
+}
+
+impl Format {
+    /// Creates output settings from options and detected terminals.
+    fn new(options: &Options) -> Self {
+        Self {
+            zero: options.zero,
+            sanitize_stdout: options.sanitize.is_enabled(&stdout()),
+            sanitize_stderr: options.sanitize.is_enabled(&stderr()),
+            block_size: options.block_size,
+            quiet: options.quiet,
+            status: options.status,
+            verbose: options.verbose,
+        }
+    }
+}
+
+/// Result of processing single input.
+struct Report<D> {
+    input: Input,
+    result: Result<Checksum<D>, Error>,
+    elapsed: Duration,
+    /// Index of worker thread which has processed input.
+    thread: Option<usize>,
+}
+
+impl<D> Report<D> {
+    /// Creates report of input processed since given instant by current thread.
+    fn new(input: Input, result: Result<Checksum<D>, Error>, start: Instant) -> Self {
+        let elapsed = start.elapsed();
+        let thread = rayon::current_thread_index();
+        Self {
+            input,
+            result,
+            elapsed,
+            thread,
+        }
+    }
+}
+
+/// Message sent to the printer thread.
+enum Message<D> {
+    /// Input is being processed, which is shown by progress.
+    Started(Input),
+    /// Input has been processed.
+    Report(Report<D>),
+    /// Details of processing input, printed in verbose mode.
+    Log(Input, String),
+    /// Processing has been stopped after given number of errors, leaving given number of inputs unprocessed.
+    Stopped(u64, u64),
+    /// Processing has been interrupted by signal, leaving given number of inputs unprocessed.
+    Interrupted(u64),
+}
+
+/// Statistics of all processed inputs.
+struct Statistics {
+    start: Instant,
+    succeeded: u64,
+    partial: u64,
+    failed: u64,
+    bytes: u64,
+}
+
+impl Statistics {
+    /// Creates statistics starting now.
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            succeeded: 0,
+            partial: 0,
+            failed: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Updates statistics with processed input.
+    fn add<D>(&mut self, report: &Report<D>) {
+        match &report.result {
+            Ok(checksum) if checksum.failure.is_some() => {
+                self.failed += 1;
+                self.bytes += checksum.bytes;
+            },
+            Ok(checksum) => {
+                self.succeeded += 1;
+                if !checksum.skipped.is_empty() {
+                    self.partial += 1;
+                }
+                self.bytes += checksum.bytes;
+            },
+            Err(_) => self.failed += 1,
+        }
+    }
+}
+
+/// Writes input name of stdout record.
+fn write_input(stdout: &mut impl Write, input: &Input, format: Format) -> io::Result<()> {
+    match input {
+        // NUL-delimited records carry raw paths
+        Input::Path(path) if format.zero && !format.sanitize_stdout => stdout.write_all(&raw(path)),
+        Input::Range(path, range) if format.zero && !format.sanitize_stdout => {
+            stdout.write_all(&raw(path))?;
+            write!(stdout, "{range}")
+        },
+        input => {
+            // like GNU tools, records with escaped paths start with a backslash, so plain paths are read back as-is
+            if !format.zero && input.path().is_some_and(|path| is_escaped(path, format)) {
+                stdout.write_all(b"\\")?;
+            }
+            if format.sanitize_stdout {
+                write!(stdout, "{}", sanitize(&input.to_string()))
+            } else {
+                write!(stdout, "{input}")
+            }
+        },
+    }
+}
+
+/// Checks whether path is printed escaped in stdout record.
+fn is_escaped(path: &Path, format: Format) -> bool {
+    match escape(path) {
+        Cow::Owned(_) => true,
+        Cow::Borrowed(path) => format.sanitize_stdout && matches!(sanitize(path), Cow::Owned(_)),
+    }
 }
 
 /// Prints result to stdout or stderr.
 fn print_result(
     stdout: &mut impl Write,
     stderr: &mut impl Write,
-    input: Input,
-    result: Result<impl Digest, Error>,
+    report: Report<impl Digest>,
+    format: Format,
 ) -> io::Result<()> {
+    let Report {
+        input,
+        result,
+        elapsed,
+        thread,
+    } = report;
+    if format.status {
+        return Ok(());
+    }
+    let terminator = if format.zero { '\0' } else { '\n' };
     match result {
-        Ok(digest) => writeln!(stdout, "{input}: {digest}"),
-        Err(error) => {
-            let error = error.to_string().to_lowercase();
-            let error = format!("{input}: {error}");
-            #[cfg(feature = "color")]
-            let error = error.red();
-            writeln!(stderr, "{error}")
+        Ok(Checksum {
+            digest,
+            bytes,
+            holes,
+            blocks,
+            skipped,
+            failure,
+        }) => {
+            if !format.quiet {
+                write_input(stdout, &input, format)?;
+                write!(stdout, ": {digest}{terminator}")?;
+            }
+            if let Some(blocks) = blocks.filter(|_| !format.quiet) {
+                // blocks are numbered from start of hashed data, offsets are absolute
+                let start = match &input {
+                    Input::Range(_, range) => range.offset,
+                    _ => 0,
+                };
+                let size = format.block_size.unwrap_or_default();
+                for (index, digest) in blocks.iter().enumerate() {
+                    write_input(stdout, &input, format)?;
+                    let offset = start + index as u64 * size;
+                    write!(stdout, "#{index}@{offset}: {digest}{terminator}")?;
+                }
+            }
+            if !skipped.is_empty() {
+                // flush stdout first, so skipped entries don't overtake the result
+                stdout.flush()?;
+                for error in &skipped {
+                    let error = describe(error);
+                    print_log(stderr, &input, &format!("skipped {error}"), format)?;
+                }
+                let count = skipped.len();
+                let entries = if count == 1 { "entry" } else { "entries" };
+                print_log(
+                    stderr,
+                    &input,
+                    &format!("digest is partial, {count} {entries} skipped"),
+                    format,
+                )?;
+            }
+            if let Some(failure) = failure {
+                // flush stdout first, so failure doesn't overtake the result
+                stdout.flush()?;
+                print_error(stderr, &input, &failure, format)?;
+            }
+            if format.verbose > 0 {
+                // flush stdout first, so details don't overtake the result
+                stdout.flush()?;
+                let seconds = elapsed.as_secs_f64();
+                let throughput = if seconds > 0.0 {
+                    bytes as f64 / seconds / f64::from(1 << 20)
+                } else {
+                    0.0
+                };
+                let allocated = if holes > 0 {
+                    format!(" ({} allocated)", bytes - holes)
+                } else {
+                    String::new()
+                };
+                let thread = match thread {
+                    Some(thread) => format!(" on thread {thread}"),
+                    None => String::new(),
+                };
+                let details = format!("read {bytes} bytes{allocated} in {seconds:.3}s ({throughput:.1} MiB/s){thread}");
+                print_log(stderr, &input, &details, format)?;
+            }
+            Ok(())
+        },
+        Err(error) => print_error(stderr, &input, &error, format),
+    }
+}
+
+/// Prints error of input to stderr.
+fn print_error(stderr: &mut impl Write, input: &Input, error: &Error, format: Format) -> io::Result<()> {
+    let error = describe(error);
+    let mut error = format!("{input}: {error}");
+    if format.sanitize_stderr {
+        error = sanitize(&error).into_owned();
+    }
+    #[cfg(feature = "color")]
+    let error = error.red();
+    writeln!(stderr, "{error}")
+}
+
+/// Prints details of processing input to stderr.
+fn print_log(stderr: &mut impl Write, input: &Input, details: &str, format: Format) -> io::Result<()> {
+    let mut details = format!("{input}: {details}");
+    if format.sanitize_stderr {
+        details = sanitize(&details).into_owned();
+    }
+    writeln!(stderr, "{details}")
+}
+
+/// Prints summary of processing stopped by `--fail-fast` or `--max-errors`.
+fn print_stopped(stderr: &mut impl Write, errors: u64, unprocessed: u64, format: Format) -> io::Result<()> {
+    if format.status {
+        return Ok(());
+    }
+    let errors = if errors == 1 {
+        "1 error".to_string()
+    } else {
+        format!("{errors} errors")
+    };
+    let inputs = if unprocessed == 1 { "input" } else { "inputs" };
+    writeln!(stderr, "stopped after {errors}, {unprocessed} {inputs} not processed")
+}
+
+/// Prints summary of processing interrupted by signal.
+fn print_interrupted(stderr: &mut impl Write, unprocessed: u64, format: Format) -> io::Result<()> {
+    if format.status {
+        return Ok(());
+    }
+    let inputs = if unprocessed == 1 { "input" } else { "inputs" };
+    writeln!(stderr, "interrupted, {unprocessed} {inputs} not processed")
+}
+
+/// Prints progress requested by SIGUSR1 to stderr.
+fn print_progress(stderr: &mut impl Write, progress: &Progress, format: Format) -> io::Result<()> {
+    if format.status {
+        return Ok(());
+    }
+    let mut line = format!("progress: {}", progress.line());
+    if format.sanitize_stderr {
+        line = sanitize(&line).into_owned();
+    }
+    writeln!(stderr, "{line}")
+}
+
+/// Prints statistics of all processed inputs to stderr.
+fn print_summary(stderr: &mut impl Write, statistics: &Statistics) -> io::Result<()> {
+    let Statistics {
+        start,
+        succeeded,
+        partial,
+        failed,
+        bytes,
+    } = statistics;
+    let inputs = match succeeded + failed {
+        1 => "1 input".to_string(),
+        inputs => format!("{inputs} inputs"),
+    };
+    let seconds = start.elapsed().as_secs_f64();
+    let throughput = if seconds > 0.0 {
+        *bytes as f64 / seconds / f64::from(1 << 20)
+    } else {
+        0.0
+    };
+    let partial = if *partial > 0 {
+        format!(" ({partial} partial)")
+    } else {
+        String::new()
+    };
+    writeln!(
+        stderr,
+        "summary: {inputs}, {succeeded} succeeded{partial}, {failed} failed, {bytes} bytes in {seconds:.3}s \
+         ({throughput:.1} MiB/s)"
+    )
+}
+
+/// Inputs in order of command line, failed ones are paired with their error.
+type Inputs<'a> = Box<dyn Iterator<Item = Result<Input, (Input, io::Error)>> + Send + 'a>;
+
+/// Assembles inputs from command line, stdin is given to a single input which reads it.
+fn inputs<'a>(args: &Args, options: &'a Options, range: Option<ByteRange>) -> Inputs<'a> {
+    let stdin = Input::Stdin(options.stdin_name.clone());
+    // stdin can be read only once, also `--files-from -` reads it
+    let mut stdin_used = options.files_from.as_deref() == Some(Path::new("-"));
+    let paths: Vec<_> = options
+        .stdin
+        .then(|| PathBuf::from("-"))
+        .into_iter()
+        .chain(args.paths.iter().cloned())
+        .map(|path| {
+            if path != Path::new("-") {
+                return Ok(Input::new(path, range));
+            }
+            if stdin_used {
+                let error = io::Error::new(io::ErrorKind::InvalidInput, "stdin is used more than once");
+                return Err((stdin.clone(), error));
+            }
+            stdin_used = true;
+            if range.is_some() {
+                let error = io::Error::new(io::ErrorKind::InvalidInput, "byte range of stdin is not supported");
+                return Err((stdin.clone(), error));
+            }
+            Ok(stdin.clone())
+        })
+        .collect();
+    let literals = options.strings.iter().cloned().map(Input::String);
+    let literals = literals.chain(options.hex_inputs.iter().cloned().map(Input::Hex));
+    // command cannot compete with other inputs for stdin
+    let command = (!options.command.is_empty()).then(|| Input::Command(options.command.clone(), !stdin_used));
+    let inputs = paths.into_iter().chain(literals.chain(command).map(move |input| {
+        if range.is_some() {
+            let error = io::Error::new(io::ErrorKind::InvalidInput, "byte range is supported only for files");
+            return Err((input, error));
+        }
+        Ok(input)
+    }));
+    match &options.files_from {
+        Some(files_from) => {
+            match FilesFrom::open(files_from, options.null) {
+                Ok(listed) => Box::new(inputs.chain(listed.map(move |path| path.map(|path| Input::new(path, range))))),
+                Err(error) => Box::new(inputs.chain([Err(error)])),
+            }
         },
+        None => Box::new(inputs),
     }
 }
 
@@ -135,52 +679,182 @@ where
 {
     let (tx, rx) = mpsc::sync_channel(1);
 
+    let format = Format::new(options);
+    let reader = Reader::new(options);
+    let range = ByteRange::new(options.offset, options.length);
+
+    let summary = options.summary;
+    // progress line is shown only on terminal, which can redraw it
+    let bar = options.progress && !options.status && stderr().is_terminal();
+    // if handlers cannot be registered, signals keep their default behavior
+    let _ = signal::register();
     let printer = thread::spawn(move || {
         let mut stdout = stdout().lock();
         let mut stderr = stderr().lock();
-        while let Ok(pair) = rx.recv() {
-            let (input, result) = pair;
-            print_result(&mut stdout, &mut stderr, input, result).expect("Cannot print result");
+        let mut statistics = Statistics::new();
+        // progress is tracked even without line, it can be requested by SIGUSR1
+        let mut progress = Progress::new();
+        loop {
+            let message = match rx.recv_timeout(progress::INTERVAL) {
+                Ok(message) => Some(message),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let report = signal::take_report();
+            if report || !matches!(message, None | Some(Message::Started(_))) {
+                // progress line is cleared before anything else is printed
+                progress.clear(&mut stderr).expect("Cannot print progress");
+            }
+            match message {
+                None => {},
+                Some(Message::Started(input)) => progress.started(input),
+                Some(Message::Report(report)) => {
+                    statistics.add(&report);
+                    progress.finished(&report.input);
+                    print_result(&mut stdout, &mut stderr, report, format).expect("Cannot print result");
+                },
+                Some(Message::Log(input, details)) => {
+                    print_log(&mut stderr, &input, &details, format).expect("Cannot print details");
+                },
+                Some(Message::Stopped(errors, unprocessed)) => {
+                    print_stopped(&mut stderr, errors, unprocessed, format).expect("Cannot print summary");
+                },
+                Some(Message::Interrupted(unprocessed)) => {
+                    print_interrupted(&mut stderr, unprocessed, format).expect("Cannot print summary");
+                },
+            }
+            if report {
+                // flush stdout first, so report comes after printed results
+                stdout.flush().expect("Cannot print result");
+                print_progress(&mut stderr, &progress, format).expect("Cannot print progress");
+            }
+            if bar && progress.is_due() {
+                // flush stdout first, so results are not drawn over
+                stdout.flush().expect("Cannot print result");
+                progress.draw(&mut stderr).expect("Cannot print progress");
+            }
+        }
+        progress.clear(&mut stderr).expect("Cannot print progress");
+        if summary {
+            // flush stdout first, so summary comes after all results
+            stdout.flush().expect("Cannot print summary");
+            print_summary(&mut stderr, &statistics).expect("Cannot print summary");
         }
     });
 
-    let rc = if options.stdin {
-        let handle = stdin().lock();
-        let result = chksum::<T>(handle);
-        let rc = exitcode(&result);
-        let pair = (Input::Stdin, result);
-        tx.send(pair).expect("Cannot send result to printer thread");
-        rc
-    } else {
-        args.paths
-            .par_iter()
-            .map(|path| {
-                let result = chksum::<T>(path);
-                let rc = exitcode(&result);
-                let pair = (path.into(), result);
-                tx.send(pair).expect("Cannot send result to printer thread");
-                rc
+    let outcome = {
+        let inputs = inputs(args, options, range);
+        let inputs: Inputs<'_> = if bar && options.prescan {
+            // all inputs must be known before hashing to sum sizes of their paths
+            let inputs: Vec<_> = inputs.collect();
+            let prescanned = inputs
+                .iter()
+                .filter_map(|input| Some(input.as_ref().ok()?.path()?.to_path_buf()))
+                .collect();
+            progress::prescan(prescanned, range, Filter::new(options), options.special_files);
+            Box::new(inputs.into_iter())
+        } else {
+            inputs
+        };
+        let max_errors = if options.fail_fast { Some(1) } else { options.max_errors };
+        let errors = AtomicU64::new(0);
+        let unprocessed = AtomicU64::new(0);
+        let outcome = inputs
+            // index of input is kept, because order of processing is arbitrary
+            .enumerate()
+            .par_bridge()
+            .map(|(index, input)| {
+                // remaining inputs are only counted once error limit is reached or signal is received
+                if signal::stopped() {
+                    unprocessed.fetch_add(1, Ordering::Relaxed);
+                    return Outcome::new();
+                }
+                let start = Instant::now();
+                let (input, result) = match input {
+                    Ok(input) => {
+                        tx.send(Message::Started(input.clone()))
+                            .expect("Cannot send input to printer thread");
+                        let log = |event: Event<'_>| {
+                            let details = match event {
+                                Event::Retry(error, delay) if format.verbose > 0 => {
+                                    let error = describe(error);
+                                    format!("{error}, retrying in {:.3}s", delay.as_secs_f64())
+                                },
+                                Event::Directory(path, entries) if format.verbose > 1 => {
+                                    format!("reading directory {} with {entries} entries", escape(path))
+                                },
+                                Event::Skip(path, reason) if format.verbose > 1 => {
+                                    format!("skipping {}: {reason}", escape(path))
+                                },
+                                _ => return,
+                            };
+                            tx.send(Message::Log(input.clone(), details))
+                                .expect("Cannot send details to printer thread");
+                        };
+                        let result = match &input {
+                            Input::Path(path) | Input::Range(path, _) => reader.chksum::<T>(path, log),
+                            Input::Stdin(_) => reader.chksum_stdin::<T>(),
+                            Input::String(text) => reader.chksum_bytes::<T>(text.as_bytes()),
+                            Input::Hex(hex) => reader.chksum_bytes::<T>(hex.as_bytes()),
+                            Input::Command(command, stdin) => reader.chksum_command::<T>(command, *stdin),
+                        };
+                        (input, result)
+                    },
+                    Err((input, error)) => (input, Err(error.into())),
+                };
+                if result.as_ref().is_err_and(signal::is_stopped) {
+                    // hashing has been stopped midway, input is reported as not processed
+                    unprocessed.fetch_add(1, Ordering::Relaxed);
+                    return Outcome::new();
+                }
+                let outcome = match &result {
+                    Ok(Checksum {
+                        failure: Some(failure), ..
+                    }) => Outcome::of(index, Status::of_error(failure)),
+                    Ok(checksum) if !checksum.skipped.is_empty() => Outcome::of(index, Status::Partial),
+                    result => Outcome::of(index, status(result)),
+                };
+                // digest of failed command counts as error too
+                if !result.as_ref().is_ok_and(|checksum| checksum.failure.is_none()) {
+                    let errors = errors.fetch_add(1, Ordering::Relaxed) + 1;
+                    // inputs which are being processed are abandoned too
+                    if max_errors.is_some_and(|max_errors| errors >= max_errors) {
+                        signal::stop();
+                    }
+                }
+                let report = Report::new(input, result, start);
+                tx.send(Message::Report(report)).expect("Cannot send result to printer thread");
+                outcome
             })
-            // returns first occured error
-            .reduce(|| EXITCODE_OK, |acc, rc| if acc == EXITCODE_OK { rc } else { acc })
+            // keeps failure of the input given first, regardless of order of processing
+            .reduce(Outcome::new, Outcome::merge);
+        let unprocessed = unprocessed.into_inner();
+        if signal::interrupted().is_some() {
+            let message = Message::Interrupted(unprocessed);
+            tx.send(message).expect("Cannot send summary to printer thread");
+        } else if unprocessed > 0 {
+            let message = Message::Stopped(errors.into_inner(), unprocessed);
+            tx.send(message).expect("Cannot send summary to printer thread");
+        }
+        outcome
     };
 
     drop(tx); // must drop manually, otherwise rx.recv() never return an error
 
     printer.join().expect("The printer thread has panicked");
 
-    rc
+    // interrupted run exits like one killed by signal, so callers can tell it apart
+    match signal::interrupted() {
+        Some(signal) => 128 + signal,
+        None => outcome.code(options.exit_codes),
+    }
 }
 
-/// Turns result to exitcode.
-fn exitcode<T>(result: &Result<T, Error>) -> i32
-where
-    T: Digest,
-{
-    if result.is_ok() {
-        EXITCODE_OK
-    } else {
-        EXITCODE_IOERR
+/// Turns result to status.
+fn status<T>(result: &Result<T, Error>) -> Status {
+    match result {
+        Ok(_) => Status::Ok,
+        Err(error) => Status::of_error(error),
     }
 }
 
@@ -189,7 +863,7 @@ mod tests {
     use anyhow::Result;
     use assert_fs::prelude::PathChild;
     use assert_fs::TempDir;
-    use chksum::MD5;
+    use chksum::{chksum, MD5};
 
     use super::*;
 
@@ -198,7 +872,7 @@ mod tests {
         let tmpdir = TempDir::new()?;
 
         let result = chksum::<MD5>(tmpdir.path());
-        assert_eq!(exitcode(&result), EXITCODE_OK);
+        assert_eq!(status(&result).code(), exitcode::OK);
 
         Ok(())
     }
@@ -209,8 +883,14 @@ mod tests {
         let child = tmpdir.child("child");
 
         let result = chksum::<MD5>(child.path());
-        assert_eq!(exitcode(&result), EXITCODE_IOERR);
+        assert_eq!(status(&result).code(), exitcode::NOINPUT);
 
         Ok(())
     }
+
+    #[test]
+    fn exitcode_changed() {
+        let result: Result<(), Error> = Err(changed::changed_error());
+        assert_eq!(status(&result).code(), exitcode::TEMPFAIL);
+    }
 }
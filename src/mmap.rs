@@ -0,0 +1,57 @@
+use std::fs::File;
+use std::io;
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use memmap2::Mmap as Map;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mmap {
+    Always,
+    Auto,
+    Never,
+}
+
+impl Mmap {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Auto => "auto",
+            Self::Never => "never",
+        }
+    }
+}
+
+impl ValueEnum for Mmap {
+    fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
+        let input = if ignore_case {
+            input.to_lowercase()
+        } else {
+            input.to_string()
+        };
+        match &input[..] {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err("unrecognized option".to_string()),
+        }
+    }
+
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Always, Self::Auto, Self::Never]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let possible_value = PossibleValue::new(self.as_str());
+        Some(possible_value)
+    }
+}
+
+/// Maps whole file into memory.
+#[allow(unsafe_code)]
+pub(crate) fn map(file: &File) -> io::Result<Map> {
+    // SAFETY: the mapping is read-only and dropped right after hashing. Truncating the file meanwhile may raise
+    // SIGBUS and concurrent writes may be observed, that is why memory-mapped I/O is an opt-in feature.
+    unsafe { Map::map(file) }
+}
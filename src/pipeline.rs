@@ -0,0 +1,70 @@
+use std::io::{self, Read};
+use std::sync::mpsc;
+use std::thread;
+
+use chksum::{Error, Hash};
+
+use crate::read::{Buffer, Hasher};
+use crate::signal;
+
+/// Number of buffers circulating between reader and hasher threads.
+const BUFFERS: usize = 2;
+
+/// Updates hasher with data read on a separate thread, so reading and hashing overlap.
+pub(crate) fn pipeline<T>(
+    mut reader: impl Read + Send,
+    hasher: &mut Hasher<T>,
+    buffer_size: usize,
+    aligned: bool,
+) -> Result<(), Error>
+where
+    T: Hash,
+{
+    let (filled_tx, filled_rx) = mpsc::sync_channel::<io::Result<(Buffer, usize)>>(BUFFERS);
+    let (empty_tx, empty_rx) = mpsc::sync_channel::<Buffer>(BUFFERS);
+    for _ in 0..BUFFERS {
+        let buffer = Buffer::new(buffer_size, aligned);
+        empty_tx.send(buffer).expect("Cannot send buffer to reader thread");
+    }
+
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            for mut buffer in empty_rx {
+                let length = loop {
+                    match reader.read(buffer.as_mut_slice()) {
+                        // stopped reader would fail again and again
+                        Err(error) if error.kind() == io::ErrorKind::Interrupted && !signal::stopped() => continue,
+                        result => break result,
+                    }
+                };
+                let end = matches!(length, Ok(0) | Err(_));
+                if filled_tx.send(length.map(|length| (buffer, length))).is_err() || end {
+                    break;
+                }
+            }
+        });
+
+        let mut result = Ok(());
+        for filled in &filled_rx {
+            if signal::stopped() {
+                result = Err(signal::stopped_error().into());
+                break;
+            }
+            match filled {
+                Ok((_, 0)) => break,
+                Ok((mut buffer, length)) => {
+                    hasher.update(&buffer.as_mut_slice()[..length]);
+                    // reader thread is gone only after an error which is received next
+                    let _ = empty_tx.send(buffer);
+                },
+                Err(error) => {
+                    result = Err(error.into());
+                    break;
+                },
+            }
+        }
+        // must drop manually, otherwise reader thread may wait for a buffer forever
+        drop(empty_tx);
+        result
+    })
+}
@@ -0,0 +1,213 @@
+use std::fs::{read_dir, Metadata};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::filter::Filter;
+use crate::range::ByteRange;
+use crate::special::{is_special, SpecialFiles};
+use crate::Input;
+
+/// Interval of progress redraws.
+pub(crate) const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximal width of progress line, longer lines would wrap and break redrawing.
+const WIDTH: usize = 79;
+
+/// Number of bytes hashed by all threads.
+static HASHED: AtomicU64 = AtomicU64::new(0);
+
+/// Number of bytes of all inputs, known after pre-scan.
+static TOTAL: OnceLock<u64> = OnceLock::new();
+
+/// Adds bytes to number of hashed bytes.
+pub(crate) fn add_hashed(bytes: u64) {
+    HASHED.fetch_add(bytes, Ordering::Relaxed);
+}
+
+/// Sums sizes of inputs on a background thread, so percentage and ETA can be shown once it finishes.
+///
+/// Directory entries are counted only when they pass filters and special files policy.
+pub(crate) fn prescan(paths: Vec<PathBuf>, range: Option<ByteRange>, filter: Filter, special_files: SpecialFiles) {
+    // thread isn't joined, it must not delay the end of processing
+    thread::spawn(move || {
+        let total = paths
+            .iter()
+            .filter_map(|path| {
+                let metadata = path.metadata().ok()?;
+                Some(size(path, &metadata, range, &filter, special_files))
+            })
+            .sum();
+        let _ = TOTAL.set(total);
+    });
+}
+
+/// Progress drawn on stderr by the printer thread, or reported on request.
+pub(crate) struct Progress {
+    start: Instant,
+    /// Inputs which are being processed.
+    active: Vec<Input>,
+    /// Time of last redraw.
+    drawn: Instant,
+    /// Line is currently shown.
+    visible: bool,
+}
+
+impl Progress {
+    /// Creates progress starting now.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            active: Vec::new(),
+            drawn: Instant::now(),
+            visible: false,
+        }
+    }
+
+    /// Marks input as being processed.
+    pub fn started(&mut self, input: Input) {
+        self.active.push(input);
+    }
+
+    /// Marks input as processed.
+    pub fn finished(&mut self, input: &Input) {
+        if let Some(index) = self.active.iter().position(|active| active == input) {
+            self.active.remove(index);
+        }
+    }
+
+    /// Checks whether line should be redrawn.
+    pub fn is_due(&self) -> bool {
+        self.drawn.elapsed() >= INTERVAL
+    }
+
+    /// Draws progress line over the previous one.
+    pub fn draw(&mut self, stderr: &mut impl Write) -> io::Result<()> {
+        // control characters would break the line
+        let line: String = self
+            .line()
+            .chars()
+            .filter(|char| !char.is_control())
+            .take(WIDTH)
+            .collect();
+        write!(stderr, "\r{line}\x1b[K")?;
+        stderr.flush()?;
+        self.drawn = Instant::now();
+        self.visible = true;
+        Ok(())
+    }
+
+    /// Formats hashed bytes, throughput, ETA and processed inputs.
+    pub fn line(&self) -> String {
+        let hashed = HASHED.load(Ordering::Relaxed);
+        let seconds = self.start.elapsed().as_secs_f64();
+        let throughput = if seconds > 0.0 { hashed as f64 / seconds } else { 0.0 };
+        let mut line = format!("{:.1} MiB", mebibytes(hashed));
+        let total = TOTAL.get().copied();
+        if let Some(total) = total {
+            let percent = if total > 0 {
+                (hashed as f64 / total as f64 * 100.0).min(100.0)
+            } else {
+                100.0
+            };
+            line.push_str(&format!(" / {:.1} MiB ({percent:.0}%)", mebibytes(total)));
+        }
+        line.push_str(&format!(", {:.1} MiB/s", mebibytes(throughput as u64)));
+        if let Some(total) = total.filter(|_| throughput > 0.0) {
+            let eta = (total.saturating_sub(hashed) as f64 / throughput) as u64;
+            line.push_str(&format!(", ETA {}:{:02}", eta / 60, eta % 60));
+        }
+        if let Some(input) = self.active.first() {
+            line.push_str(&format!(", {input}"));
+            if self.active.len() > 1 {
+                line.push_str(&format!(" (+{} more)", self.active.len() - 1));
+            }
+        }
+        line
+    }
+
+    /// Clears progress line, so other output can be printed.
+    pub fn clear(&mut self, stderr: &mut impl Write) -> io::Result<()> {
+        if self.visible {
+            write!(stderr, "\r\x1b[K")?;
+            stderr.flush()?;
+            self.visible = false;
+        }
+        Ok(())
+    }
+}
+
+/// Converts bytes to mebibytes.
+fn mebibytes(bytes: u64) -> f64 {
+    bytes as f64 / f64::from(1 << 20)
+}
+
+/// Returns number of bytes which are hashed for path, errors are ignored.
+fn size(
+    path: &Path,
+    metadata: &Metadata,
+    range: Option<ByteRange>,
+    filter: &Filter,
+    special_files: SpecialFiles,
+) -> u64 {
+    if metadata.is_dir() {
+        let Ok(dir_entries) = read_dir(path) else {
+            return 0;
+        };
+        return dir_entries
+            .filter_map(Result::ok)
+            .filter_map(|dir_entry| {
+                let path = dir_entry.path();
+                let metadata = path.metadata().ok()?;
+                // skipped and rejected entries aren't hashed
+                let hashed = (!is_special(&metadata) || special_files == SpecialFiles::Read)
+                    && filter.matches(&path, &metadata).unwrap_or(false);
+                hashed.then(|| size(&path, &metadata, None, filter, special_files))
+            })
+            .sum();
+    }
+    let length = metadata.len();
+    match range {
+        Some(range) => {
+            let length = length.saturating_sub(range.offset);
+            range.length.map_or(length, |range| range.min(length))
+        },
+        None => length,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_format() {
+        let mut progress = Progress::new();
+        assert_eq!(progress.line(), "0.0 MiB, 0.0 MiB/s");
+
+        // ETA is known only after pre-scan
+        progress.start -= Duration::from_secs(10);
+        add_hashed(10 << 20);
+        assert_eq!(progress.line(), "10.0 MiB, 1.0 MiB/s");
+
+        TOTAL.set(100 << 20).unwrap();
+        assert_eq!(progress.line(), "10.0 MiB / 100.0 MiB (10%), 1.0 MiB/s, ETA 1:30");
+
+        progress.started(Input::from("first"));
+        progress.started(Input::from("second"));
+        progress.started(Input::from("third"));
+        assert_eq!(
+            progress.line(),
+            "10.0 MiB / 100.0 MiB (10%), 1.0 MiB/s, ETA 1:30, first (+2 more)"
+        );
+        progress.finished(&Input::from("first"));
+        progress.finished(&Input::from("third"));
+        assert_eq!(
+            progress.line(),
+            "10.0 MiB / 100.0 MiB (10%), 1.0 MiB/s, ETA 1:30, second"
+        );
+    }
+}
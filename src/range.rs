@@ -0,0 +1,123 @@
+use std::cmp::min;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read};
+
+/// Byte range of file selected with `--offset` and `--length`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct ByteRange {
+    pub offset: u64,
+    pub length: Option<u64>,
+}
+
+impl ByteRange {
+    /// Creates range, whole file is not considered a range.
+    pub fn new(offset: Option<u64>, length: Option<u64>) -> Option<Self> {
+        match (offset, length) {
+            (None | Some(0), None) => None,
+            (offset, length) => {
+                let offset = offset.unwrap_or_default();
+                Some(Self { offset, length })
+            },
+        }
+    }
+
+    /// Returns part of data covered by range.
+    #[cfg(feature = "mmap")]
+    pub fn slice<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        let start = usize::try_from(self.offset).unwrap_or(usize::MAX).min(data.len());
+        let data = &data[start..];
+        match self.length {
+            Some(length) => &data[..usize::try_from(length).unwrap_or(usize::MAX).min(data.len())],
+            None => data,
+        }
+    }
+}
+
+impl Display for ByteRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.length {
+            Some(length) => write!(f, "[{}..{}]", self.offset, self.offset.saturating_add(length)),
+            None => write!(f, "[{}..]", self.offset),
+        }
+    }
+}
+
+/// Reader which skips leading bytes and stops after given number of bytes.
+///
+/// Inner reader is always asked for whole buffer, so reads stay aligned for `O_DIRECT`.
+pub(crate) struct RangeReader<R> {
+    inner: R,
+    skip: u64,
+    remaining: Option<u64>,
+}
+
+impl<R> RangeReader<R> {
+    /// Creates reader which skips `skip` bytes and then reads at most `length` bytes.
+    pub fn new(inner: R, skip: u64, length: Option<u64>) -> Self {
+        Self {
+            inner,
+            skip,
+            remaining: length,
+        }
+    }
+}
+
+impl<R> Read for RangeReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.remaining == Some(0) {
+                return Ok(0);
+            }
+            let length = self.inner.read(buffer)?;
+            if length == 0 {
+                return Ok(0);
+            }
+            let skipped = min(self.skip, length as u64) as usize;
+            self.skip -= skipped as u64;
+            if skipped == length {
+                continue;
+            }
+            if skipped > 0 {
+                buffer.copy_within(skipped..length, 0);
+            }
+            let mut length = length - skipped;
+            if let Some(remaining) = &mut self.remaining {
+                length = min(length as u64, *remaining) as usize;
+                *remaining -= length as u64;
+            }
+            return Ok(length);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_reader() {
+        let data = b"0123456789";
+
+        let mut reader = RangeReader::new(&data[..], 2, Some(5));
+        let mut buffer = [0; 3];
+        let mut read = Vec::new();
+        loop {
+            let length = reader.read(&mut buffer).unwrap();
+            if length == 0 {
+                break;
+            }
+            read.extend_from_slice(&buffer[..length]);
+        }
+        assert_eq!(read, b"23456");
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(ByteRange::new(None, None), None);
+        assert_eq!(ByteRange::new(Some(512), None).unwrap().to_string(), "[512..]");
+        assert_eq!(ByteRange::new(None, Some(16)).unwrap().to_string(), "[0..16]");
+    }
+}
@@ -0,0 +1,580 @@
+use std::cmp::min;
+use std::ffi::OsString;
+use std::fs::{read_dir, DirEntry, Metadata};
+use std::io::{self, stdin, IsTerminal, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::{self, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use chksum::{Error, Hash};
+
+use crate::changed::{changed_error, is_changed, Snapshot};
+use crate::command::{command_error, CommandError};
+use crate::context::{path_error, source, strip, Context, Operation};
+use crate::filter::Filter;
+use crate::interruptible::InterruptibleReader;
+use crate::io_mode::IoMode;
+#[cfg(feature = "mmap")]
+use crate::mmap::{self, Mmap};
+use crate::pipeline::pipeline;
+use crate::range::{ByteRange, RangeReader};
+#[cfg(target_os = "linux")]
+use crate::sparse::SparseReader;
+use crate::special::{is_block_device, is_special, SpecialFiles};
+use crate::{progress, signal, Options};
+
+/// Alignment of read buffers, `O_DIRECT` requires aligned buffer, offset and length.
+const ALIGNMENT: usize = 4096;
+
+/// Settings of reading files and directories.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Reader {
+    /// The way files are opened and read.
+    io_mode: IoMode,
+    /// Smallest regular file which is memory-mapped.
+    #[cfg(feature = "mmap")]
+    mmap_threshold: Option<u64>,
+    /// Reading is done on a separate thread.
+    pipeline: bool,
+    /// Size of single read.
+    buffer_size: usize,
+    /// Part of file which is hashed.
+    range: Option<ByteRange>,
+    /// Size of blocks which are hashed separately.
+    block_size: Option<u64>,
+    /// Handling of special files found in directories.
+    special_files: SpecialFiles,
+    /// Filters of files found in directories.
+    filter: Filter,
+    /// Number of times input is hashed again when its file changes during read.
+    retry_changed: u32,
+    /// Number of times input is hashed again after transient error.
+    retries: u32,
+    /// Delay before first retry after transient error.
+    retry_delay: Duration,
+    /// Entries of directories which cannot be accessed are skipped.
+    skip_unreadable: bool,
+    /// Holes of sparse files are skipped.
+    #[cfg(target_os = "linux")]
+    sparse: bool,
+}
+
+impl Reader {
+    /// Creates reader from options.
+    pub fn new(options: &Options) -> Self {
+        #[cfg(target_os = "linux")]
+        let io_mode = options.io_mode;
+        #[cfg(not(target_os = "linux"))]
+        let io_mode = IoMode::Normal;
+        #[cfg(feature = "mmap")]
+        let mmap_threshold = match options.mmap {
+            Mmap::Always => Some(0),
+            Mmap::Auto => Some(options.mmap_threshold),
+            Mmap::Never => None,
+        };
+        Self {
+            io_mode,
+            #[cfg(feature = "mmap")]
+            mmap_threshold,
+            pipeline: options.pipeline,
+            buffer_size: options.buffer_size,
+            range: ByteRange::new(options.offset, options.length),
+            block_size: options.block_size,
+            special_files: options.special_files,
+            filter: Filter::new(options),
+            retry_changed: options.retry_changed,
+            retries: options.retries,
+            retry_delay: options.retry_delay,
+            skip_unreadable: options.skip_unreadable,
+            #[cfg(target_os = "linux")]
+            sparse: options.sparse,
+        }
+    }
+
+    /// Calculates digest of file or directory, calling `log` with events of processing.
+    ///
+    /// Inputs changed during read are retried immediately, transient errors are retried with doubling delay.
+    pub fn chksum<T>(&self, path: &Path, mut log: impl FnMut(Event)) -> Result<Checksum<T::Digest>, Error>
+    where
+        T: Hash,
+    {
+        let mut changed = 0;
+        let mut transient = 0;
+        loop {
+            let error = match self.chksum_once::<T>(path, &mut log) {
+                // path of input itself is already known
+                Err(error) => strip(error, path),
+                result => return result,
+            };
+            let delay = if is_changed(&error) && changed < self.retry_changed {
+                changed += 1;
+                Duration::ZERO
+            } else if is_transient(&error) && transient < self.retries {
+                let delay = self.retry_delay.saturating_mul(1 << transient.min(16));
+                transient += 1;
+                delay
+            } else {
+                return Err(error);
+            };
+            log(Event::Retry(&error, delay));
+            thread::sleep(delay);
+        }
+    }
+
+    /// Calculates digest of file or directory without retrying.
+    fn chksum_once<T>(&self, path: &Path, log: &mut dyn FnMut(Event)) -> Result<Checksum<T::Digest>, Error>
+    where
+        T: Hash,
+    {
+        let mut hasher = Hasher::<T>::new(self.block_size);
+        let metadata = path.metadata().context(path, Operation::Stat)?;
+        // special files given explicitly are always read
+        if let Some(range) = self.range {
+            if metadata.is_dir() {
+                let error = io::Error::new(io::ErrorKind::InvalidInput, "byte range cannot be applied to directory");
+                return Err(error.into());
+            }
+            self.chksum_file(path, &metadata, Some(range), &mut hasher)?;
+        } else {
+            self.chksum_path(path, &metadata, &mut hasher, log)?;
+        }
+        Ok(hasher.finalize())
+    }
+
+    /// Calculates digest of stdin.
+    pub fn chksum_stdin<T>(&self) -> Result<Checksum<T::Digest>, Error>
+    where
+        T: Hash,
+    {
+        let stdin = stdin();
+        if stdin.is_terminal() {
+            return Err(Error::IsTerminal);
+        }
+
+        let mut hasher = Hasher::<T>::new(self.block_size);
+        self.read(InterruptibleReader::new(stdin, true), &mut hasher)?;
+        Ok(hasher.finalize())
+    }
+
+    /// Calculates digest of bytes given on command line.
+    pub fn chksum_bytes<T>(&self, bytes: &[u8]) -> Result<Checksum<T::Digest>, Error>
+    where
+        T: Hash,
+    {
+        let mut hasher = Hasher::<T>::new(self.block_size);
+        self.read(bytes, &mut hasher)?;
+        Ok(hasher.finalize())
+    }
+
+    /// Calculates digest of stdout of command, digest of command which exits unsuccessfully carries its failure.
+    ///
+    /// Command inherits stdin only when it isn't read by another input.
+    pub fn chksum_command<T>(&self, command: &[OsString], stdin: bool) -> Result<Checksum<T::Digest>, Error>
+    where
+        T: Hash,
+    {
+        let (program, args) = command.split_first().expect("Command must not be empty");
+        let stdin = if stdin { Stdio::inherit() } else { Stdio::null() };
+        let mut child = process::Command::new(program)
+            .args(args)
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| command_error(CommandError::Spawn(error)))?;
+        let stdout = child.stdout.take().expect("Stdout of child must be piped");
+
+        let mut hasher = Hasher::<T>::new(self.block_size);
+        if let Err(error) = self.read(InterruptibleReader::new(stdout, true), &mut hasher) {
+            // child could wait for reader forever, or keep running after interrupt
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(error);
+        }
+        let status = child.wait()?;
+        let mut checksum = hasher.finalize();
+        if !status.success() {
+            // output may be incomplete, digest is reported along with the failure
+            checksum.failure = Some(command_error(CommandError::Failed(status)));
+        }
+        Ok(checksum)
+    }
+
+    /// Updates hasher with file or directory.
+    fn chksum_path<T>(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        hasher: &mut Hasher<T>,
+        log: &mut dyn FnMut(Event),
+    ) -> Result<(), Error>
+    where
+        T: Hash,
+    {
+        if metadata.is_dir() {
+            self.chksum_dir(path, hasher, log)
+        } else {
+            // everything treat as a file when it is not a directory
+            self.chksum_file(path, metadata, None, hasher)
+        }
+    }
+
+    /// Updates hasher with directory entries sorted by path.
+    fn chksum_dir<T>(&self, path: &Path, hasher: &mut Hasher<T>, log: &mut dyn FnMut(Event)) -> Result<(), Error>
+    where
+        T: Hash,
+    {
+        let dir_entries: io::Result<Vec<DirEntry>> = read_dir(path).context(path, Operation::ReadDir)?.collect();
+        let mut dir_entries = dir_entries.context(path, Operation::ReadDir)?;
+        dir_entries.sort_by_key(DirEntry::path);
+        log(Event::Directory(path, dir_entries.len()));
+        dir_entries.into_iter().try_for_each(|dir_entry| {
+            let path = dir_entry.path();
+            match self.chksum_entry(&path, hasher, log) {
+                Err(error) if self.skip_unreadable && is_unreadable(&error) => {
+                    hasher.skipped.push(error);
+                    Ok(())
+                },
+                result => result,
+            }
+        })
+    }
+
+    /// Updates hasher with directory entry which passes special files policy and filters.
+    fn chksum_entry<T>(&self, path: &Path, hasher: &mut Hasher<T>, log: &mut dyn FnMut(Event)) -> Result<(), Error>
+    where
+        T: Hash,
+    {
+        let metadata = path.metadata().context(path, Operation::Stat)?;
+        if is_special(&metadata) {
+            match self.special_files {
+                SpecialFiles::Skip => {
+                    log(Event::Skip(path, "special file"));
+                    return Ok(());
+                },
+                SpecialFiles::Error => {
+                    let error = io::Error::new(io::ErrorKind::Unsupported, "special file");
+                    return Err(Error::from(error)).context(path, Operation::Open);
+                },
+                SpecialFiles::Read => {},
+            }
+        }
+        if !self.filter.matches(path, &metadata).context(path, Operation::Stat)? {
+            log(Event::Skip(path, "filtered out"));
+            return Ok(());
+        }
+        self.chksum_path(path, &metadata, hasher, log)
+    }
+
+    /// Updates hasher with content of file or its part, regular files are checked for changes during read.
+    fn chksum_file<T>(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        range: Option<ByteRange>,
+        hasher: &mut Hasher<T>,
+    ) -> Result<(), Error>
+    where
+        T: Hash,
+    {
+        self.read_file(path, metadata, range, hasher)?;
+        let changed = metadata.is_file()
+            && Snapshot::new(metadata) != Snapshot::new(&path.metadata().context(path, Operation::Stat)?);
+        if changed {
+            return Err(changed_error()).context(path, Operation::Read);
+        }
+        Ok(())
+    }
+
+    /// Updates hasher with content of file or its part.
+    #[cfg_attr(not(feature = "mmap"), allow(unused_variables))]
+    fn read_file<T>(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        range: Option<ByteRange>,
+        hasher: &mut Hasher<T>,
+    ) -> Result<(), Error>
+    where
+        T: Hash,
+    {
+        let mut file = self.io_mode.open(path).context(path, Operation::Open)?;
+        if file.file().is_terminal() {
+            return Err(Error::IsTerminal);
+        }
+        #[cfg(feature = "mmap")]
+        if let Some(threshold) = self.mmap_threshold {
+            let length = metadata.len();
+            // pipes and special files are streamed, empty files cannot be mapped,
+            // direct reads bypass the page cache which mapping relies on
+            let mappable = metadata.is_file() && length > 0 && length >= threshold && file.mode() != IoMode::Direct;
+            // sparse files are read region by region
+            #[cfg(target_os = "linux")]
+            let mappable = mappable && !self.sparse;
+            if mappable {
+                // fall back to streaming when mapping fails
+                if let Ok(map) = mmap::map(file.file()) {
+                    let data = match range {
+                        Some(range) => range.slice(&map),
+                        None => &map[..],
+                    };
+                    // hashing in chunks keeps progress going and lets signals stop it
+                    for chunk in data.chunks(self.buffer_size) {
+                        if signal::stopped() {
+                            return Err(signal::stopped_error().into());
+                        }
+                        hasher.update(chunk);
+                    }
+                    file.consumed();
+                    return Ok(());
+                }
+            }
+        }
+        // size of block device is discovered by seeking to its end
+        let size = if is_block_device(metadata) {
+            let size = file.seek(SeekFrom::End(0)).context(path, Operation::Read)?;
+            file.rewind().context(path, Operation::Read)?;
+            size
+        } else {
+            metadata.len()
+        };
+        let (skip, length) = match range {
+            Some(range) => {
+                // direct reads must start at aligned offset, bytes before range are skipped
+                let start = match file.mode() {
+                    IoMode::Direct => range.offset - range.offset % ALIGNMENT as u64,
+                    _ => range.offset,
+                };
+                // unseekable files are read from the beginning
+                let skip = match file.seek(SeekFrom::Start(start)) {
+                    Ok(_) => range.offset - start,
+                    Err(_) => range.offset,
+                };
+                (skip, range.length)
+            },
+            None => (0, None),
+        };
+        // block devices are read up to their size, even if they pretend to be longer
+        let length = match length {
+            Some(length) => Some(length),
+            None if is_block_device(metadata) => Some(size.saturating_sub(range.map_or(0, |range| range.offset))),
+            None => None,
+        };
+        #[cfg(target_os = "linux")]
+        if self.sparse && metadata.is_file() {
+            let mut holes = 0;
+            let reader = SparseReader::new(file, size, skip, length, &mut holes).context(path, Operation::Read)?;
+            let result = self
+                .read(RangeReader::new(reader, skip, length), hasher)
+                .context(path, Operation::Read);
+            hasher.holes += holes;
+            return result;
+        }
+        // pipes and devices may block until data comes
+        let reader = InterruptibleReader::new(file, !metadata.is_file());
+        self.read(RangeReader::new(reader, skip, length), hasher)
+            .context(path, Operation::Read)
+    }
+
+    /// Updates hasher with data from reader.
+    fn read<T>(&self, mut reader: impl Read + Send, hasher: &mut Hasher<T>) -> Result<(), Error>
+    where
+        T: Hash,
+    {
+        if self.pipeline {
+            return pipeline(reader, hasher, self.buffer_size, self.io_mode == IoMode::Direct);
+        }
+
+        let mut buffer = Buffer::new(self.buffer_size, self.io_mode == IoMode::Direct);
+        loop {
+            if signal::stopped() {
+                return Err(signal::stopped_error().into());
+            }
+            match reader.read(buffer.as_mut_slice()) {
+                Ok(0) => return Ok(()),
+                Ok(length) => hasher.update(&buffer.as_mut_slice()[..length]),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+/// Checks whether error has occured before any data of entry has been hashed, so entry can be skipped.
+fn is_unreadable(error: &Error) -> bool {
+    let Error::Io(error) = error else {
+        return false;
+    };
+    // rejected special files are errors by policy, they must not be skipped
+    path_error(error).is_some_and(|error| {
+        matches!(error.operation, Operation::Open | Operation::ReadDir | Operation::Stat)
+            && error.source.kind() != io::ErrorKind::Unsupported
+    })
+}
+
+/// Checks whether error is likely to disappear when reading is repeated, e.g. on network file systems.
+fn is_transient(error: &Error) -> bool {
+    let Error::Io(error) = error else {
+        return false;
+    };
+    let error = source(error);
+    #[cfg(target_os = "linux")]
+    if let Some(errno) = rustix::io::Errno::from_io_error(error) {
+        return matches!(
+            errno,
+            rustix::io::Errno::IO | rustix::io::Errno::STALE | rustix::io::Errno::AGAIN
+        );
+    }
+    error.kind() == io::ErrorKind::WouldBlock
+}
+
+/// Event of processing which can be logged.
+pub(crate) enum Event<'a> {
+    /// Input is processed again after error with given delay.
+    Retry(&'a Error, Duration),
+    /// Directory with given number of entries is walked.
+    Directory(&'a Path, usize),
+    /// Directory entry is skipped for given reason.
+    Skip(&'a Path, &'static str),
+}
+
+/// Hash state with number of bytes it has been updated with.
+pub(crate) struct Hasher<T>
+where
+    T: Hash,
+{
+    hash: T,
+    bytes: u64,
+    holes: u64,
+    blocks: Option<Blocks<T>>,
+    skipped: Vec<Error>,
+}
+
+impl<T> Hasher<T>
+where
+    T: Hash,
+{
+    /// Creates hasher with empty hash state.
+    fn new(block_size: Option<u64>) -> Self {
+        Self {
+            hash: T::default(),
+            bytes: 0,
+            holes: 0,
+            blocks: block_size.map(Blocks::new),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Updates hash state with data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hash.update(data);
+        self.bytes += data.len() as u64;
+        progress::add_hashed(data.len() as u64);
+        if let Some(blocks) = &mut self.blocks {
+            blocks.update(data);
+        }
+    }
+
+    /// Produces digest with statistics.
+    fn finalize(self) -> Checksum<T::Digest> {
+        Checksum {
+            digest: self.hash.digest(),
+            bytes: self.bytes,
+            holes: self.holes,
+            blocks: self.blocks.map(Blocks::finalize),
+            skipped: self.skipped,
+            failure: None,
+        }
+    }
+}
+
+/// Hash state of fixed-size blocks.
+struct Blocks<T>
+where
+    T: Hash,
+{
+    size: u64,
+    hash: T,
+    filled: u64,
+    digests: Vec<T::Digest>,
+}
+
+impl<T> Blocks<T>
+where
+    T: Hash,
+{
+    /// Creates hash state of blocks with given size.
+    fn new(size: u64) -> Self {
+        Self {
+            size,
+            hash: T::default(),
+            filled: 0,
+            digests: Vec::new(),
+        }
+    }
+
+    /// Updates hash state of current block, starting next blocks when needed.
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let length = min(self.size - self.filled, data.len() as u64) as usize;
+            let (block, rest) = data.split_at(length);
+            self.hash.update(block);
+            self.filled += length as u64;
+            if self.filled == self.size {
+                self.digests.push(self.hash.digest());
+                self.hash.reset();
+                self.filled = 0;
+            }
+            data = rest;
+        }
+    }
+
+    /// Produces digests of all blocks, including the last partial one.
+    fn finalize(mut self) -> Vec<T::Digest> {
+        if self.filled > 0 {
+            self.digests.push(self.hash.digest());
+        }
+        self.digests
+    }
+}
+
+/// Digest with statistics of reading.
+#[derive(Debug)]
+pub(crate) struct Checksum<D> {
+    pub digest: D,
+    pub bytes: u64,
+    /// Bytes of holes which were hashed without reading.
+    pub holes: u64,
+    /// Digests of fixed-size blocks.
+    pub blocks: Option<Vec<D>>,
+    /// Errors of directory entries which have been skipped, digest is partial when there are any.
+    pub skipped: Vec<Error>,
+    /// Failure which makes digest untrusted, e.g. unsuccessful exit of command.
+    pub failure: Option<Error>,
+}
+
+/// Read buffer which exposes aligned part of allocated memory.
+pub(crate) struct Buffer {
+    data: Vec<u8>,
+    offset: usize,
+    length: usize,
+}
+
+impl Buffer {
+    /// Allocates buffer, length is rounded up to multiple of alignment when required by direct I/O.
+    pub fn new(length: usize, aligned: bool) -> Self {
+        let length = if aligned {
+            length.div_ceil(ALIGNMENT) * ALIGNMENT
+        } else {
+            length
+        };
+        let data = vec![0; length + ALIGNMENT];
+        let offset = data.as_ptr().align_offset(ALIGNMENT);
+        Self { data, offset, length }
+    }
+
+    /// Returns aligned part of buffer.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.data[self.offset..self.offset + self.length]
+    }
+}
@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sanitize {
+    Always,
+    Auto,
+    Never,
+}
+
+impl Sanitize {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Auto => "auto",
+            Self::Never => "never",
+        }
+    }
+
+    /// Checks whether output written to given stream should be sanitized.
+    #[must_use]
+    pub fn is_enabled(&self, stream: &impl IsTerminal) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Auto => stream.is_terminal(),
+            Self::Never => false,
+        }
+    }
+}
+
+impl ValueEnum for Sanitize {
+    fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
+        let input = if ignore_case {
+            input.to_lowercase()
+        } else {
+            input.to_string()
+        };
+        match &input[..] {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err("unrecognized option".to_string()),
+        }
+    }
+
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Always, Self::Auto, Self::Never]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let possible_value = PossibleValue::new(self.as_str());
+        Some(possible_value)
+    }
+}
+
+/// Escapes control characters as `\xHH` sequences of their UTF-8 bytes.
+///
+/// Escaped text can be turned back into original bytes with [`crate::escape::unescape`].
+pub(crate) fn sanitize(text: &str) -> Cow<'_, str> {
+    if !text.contains(char::is_control) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut sanitized = String::with_capacity(text.len());
+    for char in text.chars() {
+        if char.is_control() {
+            let mut buffer = [0; 4];
+            for byte in char.encode_utf8(&mut buffer).bytes() {
+                write!(sanitized, "\\x{byte:02x}").expect("Cannot write to string");
+            }
+        } else {
+            sanitized.push(char);
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_control_characters() {
+        assert_eq!(sanitize("plain"), "plain");
+        assert_eq!(sanitize("\x1B[2J"), "\\x1b[2J");
+        assert_eq!(sanitize("tab\there"), "tab\\x09here");
+        assert_eq!(sanitize("c1\u{9B}"), "c1\\xc2\\x9b");
+    }
+}
@@ -0,0 +1,87 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use chksum::Error;
+
+use crate::context::source;
+
+/// Flags set by signal handlers.
+#[derive(Debug, Default)]
+struct Flags {
+    /// Set by the first SIGINT or SIGTERM, the next one terminates process immediately.
+    terminated: Arc<AtomicBool>,
+    /// Number of signal which has interrupted processing.
+    signal: Arc<AtomicUsize>,
+    /// Set by SIGUSR1, cleared once progress is reported.
+    report: Arc<AtomicBool>,
+}
+
+static FLAGS: OnceLock<Flags> = OnceLock::new();
+
+/// Set once error limit is reached, so inputs being processed are abandoned.
+static STOPPED: AtomicBool = AtomicBool::new(false);
+
+/// Registers handlers of SIGINT and SIGTERM, which stop processing, and SIGUSR1, which requests progress report.
+#[cfg(unix)]
+pub(crate) fn register() -> io::Result<()> {
+    use signal_hook::consts::{SIGINT, SIGTERM, SIGUSR1};
+    use signal_hook::flag;
+
+    let flags = FLAGS.get_or_init(Flags::default);
+    for signal in [SIGINT, SIGTERM] {
+        // conditional shutdown must be registered first, so it sees flag set by the previous signal
+        flag::register_conditional_shutdown(signal, 128 + signal, Arc::clone(&flags.terminated))?;
+        flag::register(signal, Arc::clone(&flags.terminated))?;
+        flag::register_usize(signal, Arc::clone(&flags.signal), signal as usize)?;
+    }
+    flag::register(SIGUSR1, Arc::clone(&flags.report))?;
+    Ok(())
+}
+
+/// Registers handlers of signals, which are not supported on this platform.
+#[cfg(not(unix))]
+pub(crate) fn register() -> io::Result<()> {
+    Ok(())
+}
+
+/// Returns number of signal which has interrupted processing.
+pub(crate) fn interrupted() -> Option<i32> {
+    let signal = FLAGS.get()?.signal.load(Ordering::Relaxed);
+    i32::try_from(signal).ok().filter(|signal| *signal != 0)
+}
+
+/// Checks whether progress report has been requested since the last check.
+pub(crate) fn take_report() -> bool {
+    FLAGS
+        .get()
+        .is_some_and(|flags| flags.report.swap(false, Ordering::Relaxed))
+}
+
+/// Stops processing of remaining inputs and inputs which are being processed.
+pub(crate) fn stop() {
+    STOPPED.store(true, Ordering::Relaxed);
+}
+
+/// Checks whether processing has been stopped by signal or by reaching error limit.
+pub(crate) fn stopped() -> bool {
+    interrupted().is_some() || STOPPED.load(Ordering::Relaxed)
+}
+
+/// Returns error of reading stopped by signal or by reaching error limit.
+pub(crate) fn stopped_error() -> io::Error {
+    let message = if interrupted().is_some() {
+        "interrupted by signal"
+    } else {
+        "stopped after error limit"
+    };
+    io::Error::new(io::ErrorKind::Interrupted, message)
+}
+
+/// Checks whether error has been returned because reading was stopped by signal or by reaching error limit.
+pub(crate) fn is_stopped(error: &Error) -> bool {
+    match error {
+        Error::Io(error) => stopped() && source(error).kind() == io::ErrorKind::Interrupted,
+        _ => false,
+    }
+}
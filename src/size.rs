@@ -0,0 +1,54 @@
+/// Parses size given in bytes with optional unit, e.g. `4096`, `64K`, `1MiB` or `2GB`.
+///
+/// Single letter units and `*iB` units are binary, `kB`, `MB`, `GB` and `TB` are decimal.
+pub(crate) fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let index = input.find(|char: char| !char.is_ascii_digit()).unwrap_or(input.len());
+    let (number, unit) = input.split_at(index);
+    let number: u64 = number.parse().map_err(|_| format!("invalid size `{input}`"))?;
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "k" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        "T" | "TiB" => 1 << 40,
+        "kB" | "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        unit => return Err(format!("unknown size unit `{unit}`")),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size `{input}` is too large"))
+}
+
+/// Parses size which must be positive.
+pub(crate) fn parse_positive_size(input: &str) -> Result<u64, String> {
+    match parse_size(input)? {
+        0 => Err("size must be positive".to_string()),
+        size => Ok(size),
+    }
+}
+
+/// Parses size of read buffer, which must be positive.
+pub(crate) fn parse_buffer_size(input: &str) -> Result<usize, String> {
+    let size = parse_positive_size(input)?;
+    usize::try_from(size).map_err(|_| format!("size `{input}` is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_units() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("4K"), Ok(4096));
+        assert_eq!(parse_size("1MiB"), Ok(1_048_576));
+        assert_eq!(parse_size("2GB"), Ok(2_000_000_000));
+        assert!(parse_size("1X").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("99999999T").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use rustix::fs::{seek, SeekFrom as SparseSeekFrom};
+use rustix::io::Errno;
+
+use crate::io_mode::IoReader;
+
+/// Reader which skips holes of sparse file and returns zeros in their place.
+pub(crate) struct SparseReader<'a> {
+    inner: IoReader,
+    /// Logical length of the file.
+    length: u64,
+    position: u64,
+    /// Start of current or next data region.
+    data: u64,
+    /// End of current data region.
+    hole: u64,
+    /// Part of the file which is hashed, holes outside of it aren't counted.
+    counted: (u64, u64),
+    /// Number of hashed bytes which weren't read because they are in holes.
+    holes: &'a mut u64,
+}
+
+impl<'a> SparseReader<'a> {
+    /// Creates reader starting at current position of file.
+    ///
+    /// Holes are counted only after `skip` bytes and up to `limit` bytes, which are kept by `RangeReader`.
+    pub fn new(
+        mut inner: IoReader,
+        length: u64,
+        skip: u64,
+        limit: Option<u64>,
+        holes: &'a mut u64,
+    ) -> io::Result<Self> {
+        let position = inner.stream_position()?;
+        let start = position.saturating_add(skip);
+        let end = limit.map_or(u64::MAX, |limit| start.saturating_add(limit));
+        let reader = Self {
+            inner,
+            length,
+            position,
+            data: position,
+            hole: position,
+            counted: (start, end),
+            holes,
+        };
+        Ok(reader)
+    }
+
+    /// Finds next data region starting at current position.
+    fn next_region(&mut self) -> io::Result<()> {
+        let offset =
+            i64::try_from(self.position).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        self.data = match seek(self.inner.file(), SparseSeekFrom::Data(offset)) {
+            Ok(data) => data,
+            // no more data, the rest of file is a hole
+            Err(Errno::NXIO) => self.length,
+            Err(error) => return Err(error.into()),
+        };
+        self.hole = if self.data < self.length {
+            let offset =
+                i64::try_from(self.data).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+            seek(self.inner.file(), SparseSeekFrom::Hole(offset))?
+        } else {
+            self.length
+        };
+        self.inner.seek(SeekFrom::Start(self.data))?;
+        Ok(())
+    }
+}
+
+impl Read for SparseReader<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.length || buffer.is_empty() {
+            return Ok(0);
+        }
+        if self.position >= self.hole {
+            self.next_region()?;
+        }
+        if self.position < self.data {
+            let length = min(buffer.len() as u64, self.data - self.position) as usize;
+            buffer[..length].fill(0);
+            let (start, end) = self.counted;
+            let hashed = min(end, self.position + length as u64).saturating_sub(self.position.max(start));
+            self.position += length as u64;
+            *self.holes += hashed;
+            return Ok(length);
+        }
+        // whole buffer is requested to keep reads aligned, the part after the region is dropped
+        let length = self.inner.read(buffer)?;
+        let length = min(length as u64, self.hole - self.position) as usize;
+        self.position += length as u64;
+        if length == 0 {
+            // file has been truncated meanwhile
+            self.length = self.position;
+        }
+        Ok(length)
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs::Metadata;
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SpecialFiles {
+    Skip,
+    Error,
+    Read,
+}
+
+impl SpecialFiles {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Error => "error",
+            Self::Read => "read",
+        }
+    }
+}
+
+impl ValueEnum for SpecialFiles {
+    fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
+        let input = if ignore_case {
+            input.to_lowercase()
+        } else {
+            input.to_string()
+        };
+        match &input[..] {
+            "skip" => Ok(Self::Skip),
+            "error" => Ok(Self::Error),
+            "read" => Ok(Self::Read),
+            _ => Err("unrecognized option".to_string()),
+        }
+    }
+
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Skip, Self::Error, Self::Read]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let possible_value = PossibleValue::new(self.as_str());
+        Some(possible_value)
+    }
+}
+
+/// Checks whether metadata describes FIFO, socket or device node.
+#[cfg(unix)]
+pub(crate) fn is_special(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    let file_type = metadata.file_type();
+    file_type.is_fifo() || file_type.is_socket() || file_type.is_char_device() || file_type.is_block_device()
+}
+
+/// Checks whether metadata describes FIFO, socket or device node.
+#[cfg(not(unix))]
+pub(crate) fn is_special(_: &Metadata) -> bool {
+    false
+}
+
+/// Checks whether metadata describes block device.
+#[cfg(unix)]
+pub(crate) fn is_block_device(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    metadata.file_type().is_block_device()
+}
+
+/// Checks whether metadata describes block device.
+#[cfg(not(unix))]
+pub(crate) fn is_block_device(_: &Metadata) -> bool {
+    false
+}
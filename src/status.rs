@@ -0,0 +1,225 @@
+use std::cmp::min_by_key;
+use std::io;
+
+use chksum::Error;
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use exitcode::{
+    DATAERR as EXITCODE_DATAERR,
+    IOERR as EXITCODE_IOERR,
+    NOINPUT as EXITCODE_NOINPUT,
+    NOPERM as EXITCODE_NOPERM,
+    OK as EXITCODE_OK,
+    OSFILE as EXITCODE_OSFILE,
+    TEMPFAIL as EXITCODE_TEMPFAIL,
+    UNAVAILABLE as EXITCODE_UNAVAILABLE,
+};
+
+use crate::changed::is_changed;
+use crate::command::is_command_failed;
+use crate::context::source;
+
+/// Outcome of processing single input, ordered from the least to the most severe.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Status {
+    Ok,
+    /// Digest skips unreadable entries.
+    Partial,
+    /// File has changed during read.
+    Changed,
+    /// File doesn't exist.
+    NotFound,
+    /// Access to file is denied.
+    Permission,
+    /// Input is a special file or terminal which cannot be hashed.
+    Special,
+    /// Input is malformed, e.g. invalid list of files or byte range.
+    Malformed,
+    /// Command cannot be run or has exited unsuccessfully.
+    Command,
+    /// Any other I/O error.
+    Io,
+}
+
+impl Status {
+    /// Classifies error.
+    pub fn of_error(error: &Error) -> Self {
+        if is_changed(error) {
+            return Self::Changed;
+        }
+        if is_command_failed(error) {
+            return Self::Command;
+        }
+        match error {
+            Error::IsTerminal => Self::Special,
+            Error::Io(error) => {
+                match source(error).kind() {
+                    io::ErrorKind::NotFound => Self::NotFound,
+                    io::ErrorKind::PermissionDenied => Self::Permission,
+                    io::ErrorKind::Unsupported => Self::Special,
+                    io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => Self::Malformed,
+                    _ => Self::Io,
+                }
+            },
+        }
+    }
+
+    /// Returns exit code of status.
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Ok => EXITCODE_OK,
+            Self::Partial => 1,
+            Self::Changed => EXITCODE_TEMPFAIL,
+            Self::NotFound => EXITCODE_NOINPUT,
+            Self::Permission => EXITCODE_NOPERM,
+            Self::Special => EXITCODE_OSFILE,
+            Self::Malformed => EXITCODE_DATAERR,
+            Self::Command => EXITCODE_UNAVAILABLE,
+            Self::Io => EXITCODE_IOERR,
+        }
+    }
+
+    /// Returns bit of status used by bitmask exit code.
+    ///
+    /// Bitmasks stay below 64, so they cannot be mistaken for usage error or interruption by signal.
+    fn bit(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::Partial => 1,
+            Self::Changed => 2,
+            Self::NotFound => 4,
+            Self::Permission => 8,
+            // both are inputs which cannot be hashed as given
+            Self::Special | Self::Malformed => 16,
+            // output of command is read like any other stream
+            Self::Command | Self::Io => 32,
+        }
+    }
+}
+
+/// Statuses of all processed inputs.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Outcome {
+    /// Index of the first failed input and its status, partial digest is kept only until a failure.
+    first: Option<(usize, Status)>,
+    worst: Status,
+    bits: i32,
+}
+
+impl Outcome {
+    /// Creates outcome of no inputs.
+    pub fn new() -> Self {
+        Self {
+            first: None,
+            worst: Status::Ok,
+            bits: 0,
+        }
+    }
+
+    /// Creates outcome of input with given index in order of inputs.
+    pub fn of(index: usize, status: Status) -> Self {
+        Self {
+            first: (status != Status::Ok).then_some((index, status)),
+            worst: status,
+            bits: status.bit(),
+        }
+    }
+
+    /// Merges outcomes of two sets of inputs, which may be processed in any order.
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        let first = match (self.first, other.first) {
+            // partial digest is not a failure, it mustn't hide later failures
+            (Some(first), Some(other)) => {
+                Some(min_by_key(first, other, |(index, status)| {
+                    (*status == Status::Partial, *index)
+                }))
+            },
+            (first, other) => first.or(other),
+        };
+        Self {
+            first,
+            worst: self.worst.max(other.worst),
+            bits: self.bits | other.bits,
+        }
+    }
+
+    /// Returns exit code following policy.
+    pub fn code(self, policy: ExitCodes) -> i32 {
+        match policy {
+            ExitCodes::First => self.first.map_or(Status::Ok, |(_, status)| status).code(),
+            ExitCodes::Worst => self.worst.code(),
+            ExitCodes::Bitmask => self.bits,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitCodes {
+    First,
+    Worst,
+    Bitmask,
+}
+
+impl ExitCodes {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::First => "first",
+            Self::Worst => "worst",
+            Self::Bitmask => "bitmask",
+        }
+    }
+}
+
+impl ValueEnum for ExitCodes {
+    fn from_str(input: &str, ignore_case: bool) -> Result<Self, String> {
+        let input = if ignore_case {
+            input.to_lowercase()
+        } else {
+            input.to_string()
+        };
+        match &input[..] {
+            "first" => Ok(Self::First),
+            "worst" => Ok(Self::Worst),
+            "bitmask" => Ok(Self::Bitmask),
+            _ => Err("unrecognized option".to_string()),
+        }
+    }
+
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::First, Self::Worst, Self::Bitmask]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let possible_value = PossibleValue::new(self.as_str());
+        Some(possible_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_policies() {
+        // inputs are merged out of order, like results of parallel processing
+        let outcome = [
+            (3, Status::Partial),
+            (0, Status::Ok),
+            (2, Status::Io),
+            (1, Status::NotFound),
+        ]
+        .into_iter()
+        .map(|(index, status)| Outcome::of(index, status))
+        .fold(Outcome::new(), Outcome::merge);
+        assert_eq!(outcome.code(ExitCodes::First), EXITCODE_NOINPUT);
+        assert_eq!(outcome.code(ExitCodes::Worst), EXITCODE_IOERR);
+        assert_eq!(outcome.code(ExitCodes::Bitmask), 1 | 4 | 32);
+
+        let outcome = Outcome::of(0, Status::Partial).merge(Outcome::of(1, Status::NotFound));
+        assert_eq!(outcome.code(ExitCodes::First), EXITCODE_NOINPUT);
+        let outcome = Outcome::of(0, Status::Partial).merge(Outcome::of(1, Status::Ok));
+        assert_eq!(outcome.code(ExitCodes::First), 1);
+    }
+}
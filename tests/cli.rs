@@ -1,4 +1,6 @@
 use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteBin, PathChild};
+use assert_fs::TempDir;
 
 mod common;
 use common::Result;
@@ -30,3 +32,205 @@ fn help() -> Result {
 
     Ok(())
 }
+
+#[test]
+fn exit_codes() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    // nonexistent path is not found, byte range of directory is malformed input
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--offset")
+        .arg("1")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT);
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--offset")
+        .arg("1")
+        .arg("--exit-codes")
+        .arg("worst")
+        .arg("nonexistent")
+        .arg(".")
+        .assert()
+        .failure()
+        .code(exitcode::DATAERR);
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--offset")
+        .arg("1")
+        .arg("--exit-codes")
+        .arg("bitmask")
+        .arg("nonexistent")
+        .arg(".")
+        .assert()
+        .failure()
+        .code(4 | 16);
+
+    // the first failure follows order of inputs, not order of processing
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .env("RAYON_NUM_THREADS", "4")
+        .arg("md5")
+        .arg("--offset")
+        .arg("1")
+        .arg(".")
+        .args((0..32).map(|index| format!("nonexistent{index}")))
+        .assert()
+        .failure()
+        .code(exitcode::DATAERR);
+
+    Ok(())
+}
+
+#[test]
+fn fail_fast() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    // single thread processes inputs one by one, so number of errors is exact
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .env("RAYON_NUM_THREADS", "1")
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--fail-fast")
+        .arg("a")
+        .arg("b")
+        .arg("c")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stderr("a: no such file or directory (os error 2)\nstopped after 1 error, 2 inputs not processed\n");
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .env("RAYON_NUM_THREADS", "1")
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--max-errors")
+        .arg("2")
+        .arg("a")
+        .arg("b")
+        .arg("c")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stderr(
+            "a: no such file or directory (os error 2)\nb: no such file or directory (os error 2)\nstopped after 2 \
+             errors, 1 input not processed\n",
+        );
+
+    // input which is being read byte by byte is abandoned once the other one fails
+    let file = tmpdir.child("file");
+    file.write_binary(&vec![0; 1 << 22])?;
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .env("RAYON_NUM_THREADS", "2")
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--buffer-size")
+        .arg("1")
+        .arg("--fail-fast")
+        .arg("file")
+        .arg("a")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stdout("")
+        .stderr("a: no such file or directory (os error 2)\nstopped after 1 error, 1 input not processed\n");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn signals() -> Result {
+    use std::fs::OpenOptions;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::{Child, Command as Process, ExitStatus, Stdio};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    // waits for exit of process, which could otherwise hang forever
+    fn wait_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<Option<ExitStatus>> {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status));
+            }
+            sleep(Duration::from_millis(10));
+        }
+        child.kill()?;
+        Ok(None)
+    }
+
+    let tmpdir = TempDir::new()?;
+
+    let fifo = tmpdir.path().join("fifo");
+    Process::new("mkfifo").arg(&fifo).status()?;
+
+    let mut child = Process::new(assert_cmd::cargo::cargo_bin("chksum"))
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("fifo")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = child.id().to_string();
+    let mut stderr = BufReader::new(child.stderr.take().expect("Stderr of child must be piped"));
+
+    // opening blocks until chksum opens fifo, its handlers are registered by then
+    let mut writer = OpenOptions::new().write(true).open(&fifo)?;
+    writer.write_all(b"data")?;
+    Process::new("kill").arg("-USR1").arg(&pid).status()?;
+    let mut line = String::new();
+    stderr.read_line(&mut line)?;
+    assert!(line.starts_with("progress: 0.0 MiB, "), "{line}");
+
+    Process::new("kill").arg("-INT").arg(&pid).status()?;
+    // blocked read gives up even though fifo stays open
+    let status = wait_timeout(&mut child, Duration::from_secs(5))?;
+    drop(writer);
+    assert_eq!(status.and_then(|status| status.code()), Some(130));
+
+    let mut rest = String::new();
+    stderr.read_to_string(&mut rest)?;
+    let output = child.wait_with_output()?;
+    assert!(output.stdout.is_empty());
+    assert_eq!(rest, "interrupted, 1 input not processed\n");
+
+    // command is killed instead of being waited for
+    let mut child = Process::new(assert_cmd::cargo::cargo_bin("chksum"))
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--exec")
+        .arg("sh")
+        .arg("-c")
+        .arg("echo data > fifo; exec sleep 60")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = child.id().to_string();
+    // command is spawned after handlers are registered
+    let mut reader = OpenOptions::new().read(true).open(&fifo)?;
+    reader.read_to_string(&mut String::new())?;
+    Process::new("kill").arg("-TERM").arg(&pid).status()?;
+    let status = wait_timeout(&mut child, Duration::from_secs(5))?;
+    assert_eq!(status.and_then(|status| status.code()), Some(143));
+    let output = child.wait_with_output()?;
+    assert!(output.stdout.is_empty());
+    assert_eq!(output.stderr, b"interrupted, 1 input not processed\n");
+
+    Ok(())
+}
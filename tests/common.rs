@@ -1,10 +1,11 @@
-use std::result;
+use std::{io, result};
 
 use assert_cmd::cargo::CargoError;
 use assert_fs::fixture::FixtureError;
 use chksum::Error as ChksumError;
 
 #[derive(Debug, thiserror::Error)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     #[error(transparent)]
     CargoError(#[from] CargoError),
@@ -12,6 +13,8 @@ pub enum Error {
     ChksumError(#[from] ChksumError),
     #[error(transparent)]
     FixtureError(#[from] FixtureError),
+    #[error(transparent)]
+    IoError(#[from] io::Error),
 }
 
 pub type Result = result::Result<(), Error>;
@@ -0,0 +1,283 @@
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use assert_fs::TempDir;
+
+mod common;
+use common::Result;
+
+#[test]
+fn files_from() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    let list = tmpdir.child("list");
+    list.write_str("file\n")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--files-from")
+        .arg(list.path())
+        .assert()
+        .success()
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn files_from_escaped() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("a\\nb");
+    file.write_str("data")?;
+
+    // plain lists, e.g. from find, are read as-is
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--files-from")
+        .arg("-")
+        .write_stdin("a\\nb\n")
+        .assert()
+        .success()
+        .stdout("\\a\\\\nb: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    // records starting with a backslash are unescaped
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--files-from")
+        .arg("-")
+        .write_stdin("\\a\\\\nb\n")
+        .assert()
+        .success()
+        .stdout("\\a\\\\nb: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Ok(())
+}
+
+#[test]
+fn files_from_stdin_null() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--files-from")
+        .arg("-")
+        .arg("--null")
+        .write_stdin("file\0")
+        .assert()
+        .success()
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Ok(())
+}
+
+#[test]
+fn files_from_nonexistent_list() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let list = tmpdir.child("list");
+
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--files-from")
+        .arg(list.path())
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT);
+
+    Ok(())
+}
+
+#[test]
+fn null_without_files_from() -> Result {
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--null")
+        .arg(".")
+        .assert()
+        .failure()
+        .code(exitcode::USAGE);
+
+    Ok(())
+}
+
+#[test]
+fn stdin_dash() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    // single thread processes inputs one by one, so output order is exact
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .env("RAYON_NUM_THREADS", "1")
+        .arg("md5")
+        .arg("-")
+        .arg("file")
+        .write_stdin("admin1")
+        .assert()
+        .success()
+        .stdout("<stdin>: e00cf25ad42683b3df678c61f42c6bda\nfile: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--stdin-name")
+        .arg("password")
+        .arg("-")
+        .write_stdin("admin1")
+        .assert()
+        .success()
+        .stdout("password: e00cf25ad42683b3df678c61f42c6bda\n");
+
+    Ok(())
+}
+
+#[test]
+fn stdin_twice() -> Result {
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--files-from")
+        .arg("-")
+        .arg("-")
+        .write_stdin("")
+        .assert()
+        .failure()
+        .code(exitcode::DATAERR)
+        .stderr("<stdin>: stdin is used more than once\n");
+
+    Ok(())
+}
+
+#[test]
+fn literals() -> Result {
+    // single thread processes inputs one by one, so output order is exact
+    Command::cargo_bin("chksum")?
+        .env("RAYON_NUM_THREADS", "1")
+        .arg("md5")
+        .arg("--sanitize")
+        .arg("never")
+        .arg("--string")
+        .arg("admin1")
+        .arg("--string")
+        .arg("say \"hi\"")
+        .arg("--hex-input")
+        .arg("61646d696e31")
+        .assert()
+        .success()
+        .stdout(
+            "\"admin1\": e00cf25ad42683b3df678c61f42c6bda\n\"say \\\"hi\\\"\": \
+             37cbf8fddc8cda72b90d2698fd9ccb41\nhex:\"61646d696e31\": e00cf25ad42683b3df678c61f42c6bda\n",
+        );
+
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--hex-input")
+        .arg("abc")
+        .assert()
+        .failure()
+        .code(exitcode::USAGE);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn command() -> Result {
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--exec")
+        .arg("printf")
+        .arg("admin1")
+        .assert()
+        .success()
+        .stdout("exec:\"printf admin1\": e00cf25ad42683b3df678c61f42c6bda\n");
+
+    // command ends with --, arguments after it are paths again
+    Command::cargo_bin("chksum")?
+        .env("RAYON_NUM_THREADS", "1")
+        .arg("md5")
+        .arg("--exec")
+        .arg("echo")
+        .arg("-n")
+        .arg("admin1")
+        .arg("--")
+        .arg("--string")
+        .arg("admin1")
+        .assert()
+        .success()
+        .stdout(
+            "\"admin1\": e00cf25ad42683b3df678c61f42c6bda\nexec:\"echo -n admin1\": e00cf25ad42683b3df678c61f42c6bda\n",
+        );
+
+    // paths after -- aren't run as command
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--")
+        .arg("printf")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stderr("printf: no such file or directory (os error 2)\n");
+
+    // digest of output is printed along with failure of command
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--exec")
+        .arg("sh")
+        .arg("-c")
+        .arg("printf admin1; exit 3")
+        .assert()
+        .failure()
+        .code(exitcode::UNAVAILABLE)
+        .stdout("exec:\"sh -c printf admin1; exit 3\": e00cf25ad42683b3df678c61f42c6bda\n")
+        .stderr("exec:\"sh -c printf admin1; exit 3\": command failed with exit status: 3\n");
+
+    // missing program is not a missing input
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--exec")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .code(exitcode::UNAVAILABLE)
+        .stdout("")
+        .stderr("exec:\"nonexistent\": cannot run command: no such file or directory (os error 2)\n");
+
+    // stdin is hashed as input, command gets empty stdin instead of racing for it
+    Command::cargo_bin("chksum")?
+        .env("RAYON_NUM_THREADS", "1")
+        .arg("md5")
+        .arg("-")
+        .arg("--exec")
+        .arg("sh")
+        .arg("-c")
+        .arg("test /dev/stdin -ef /dev/null && printf null")
+        .write_stdin("admin1")
+        .assert()
+        .success()
+        .stdout(
+            "<stdin>: e00cf25ad42683b3df678c61f42c6bda\nexec:\"sh -c test /dev/stdin -ef /dev/null && printf null\": \
+             37a6259cc0c1dae299a7866489dff0bd\n",
+        );
+
+    Ok(())
+}
@@ -0,0 +1,426 @@
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteBin, FileWriteStr, PathChild};
+use assert_fs::TempDir;
+
+mod common;
+use common::Result;
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mmap() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("dir/file");
+    file.write_str("data")?;
+
+    for mmap in ["always", "auto", "never"] {
+        Command::cargo_bin("chksum")?
+            .current_dir(tmpdir.path())
+            .arg("md5")
+            .arg("--mmap")
+            .arg(mmap)
+            .arg("dir/file")
+            .assert()
+            .success()
+            .stdout("dir/file: 8d777f385d3dfec8815d20f7496026dc\n");
+
+        Command::cargo_bin("chksum")?
+            .current_dir(tmpdir.path())
+            .arg("md5")
+            .arg("--mmap")
+            .arg(mmap)
+            .arg("dir")
+            .assert()
+            .success()
+            .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n");
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mmap_threshold() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--mmap")
+        .arg("auto")
+        .arg("--mmap-threshold")
+        .arg("1K")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--mmap-threshold")
+        .arg("1X")
+        .arg("file")
+        .assert()
+        .failure()
+        .code(exitcode::USAGE);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn io_mode() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("dir/file");
+    file.write_str("data")?;
+
+    for io_mode in ["normal", "nocache", "direct"] {
+        Command::cargo_bin("chksum")?
+            .current_dir(tmpdir.path())
+            .arg("md5")
+            .arg("--io-mode")
+            .arg(io_mode)
+            .arg("dir/file")
+            .assert()
+            .success()
+            .stdout("dir/file: 8d777f385d3dfec8815d20f7496026dc\n");
+
+        Command::cargo_bin("chksum")?
+            .current_dir(tmpdir.path())
+            .arg("md5")
+            .arg("--io-mode")
+            .arg(io_mode)
+            .arg("dir")
+            .assert()
+            .success()
+            .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pipeline() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--pipeline")
+        .arg("--buffer-size")
+        .arg("1")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--pipeline")
+        .arg("--stdin")
+        .write_stdin("data")
+        .assert()
+        .success()
+        .stdout("<stdin>: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Ok(())
+}
+
+#[test]
+fn pipeline_verbose() -> Result {
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--pipeline")
+        .arg("--verbose")
+        .arg("--stdin")
+        .write_stdin("data")
+        .assert()
+        .success()
+        .stdout("<stdin>: 8d777f385d3dfec8815d20f7496026dc\n")
+        .stderr(predicates::str::starts_with("<stdin>: read 4 bytes in "));
+
+    Ok(())
+}
+
+#[test]
+fn buffer_size_zero() -> Result {
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--buffer-size")
+        .arg("0")
+        .arg("--stdin")
+        .assert()
+        .failure()
+        .code(exitcode::USAGE);
+
+    Ok(())
+}
+
+#[test]
+fn offset_length() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("headerdatatrailer")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--offset")
+        .arg("6")
+        .arg("--length")
+        .arg("4")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file[6..10]: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--pipeline")
+        .arg("--offset")
+        .arg("13")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file[13..]: 5dae477230611a0c72536f370f2ba420\n");
+
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn offset_length_mmap() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("headerdatatrailer")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--mmap")
+        .arg("always")
+        .arg("--offset")
+        .arg("6")
+        .arg("--length")
+        .arg("4")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file[6..10]: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Ok(())
+}
+
+#[test]
+fn offset_directory() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--offset")
+        .arg("1")
+        .arg(tmpdir.path())
+        .assert()
+        .failure()
+        .code(exitcode::DATAERR);
+
+    Ok(())
+}
+
+#[test]
+fn block_size() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("datadatada")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--block-size")
+        .arg("4")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout(
+            "file: 9e1ba571fddbce7405e557b589f63878\nfile#0@0: 8d777f385d3dfec8815d20f7496026dc\nfile#1@4: \
+             8d777f385d3dfec8815d20f7496026dc\nfile#2@8: 5ca2aa845c8cd5ace6b016841f100d82\n",
+        );
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sparse() -> Result {
+    use std::fs::File;
+    use std::io::{Seek, SeekFrom, Write};
+
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    let mut handle = File::create(file.path())?;
+    handle.set_len(1 << 20)?;
+    handle.seek(SeekFrom::Start(1 << 19))?;
+    handle.write_all(b"data")?;
+    drop(handle);
+
+    let expected = Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("file")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--sparse")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sparse_range() -> Result {
+    use std::fs::File;
+
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    File::create(file.path())?.set_len(1 << 20)?;
+
+    // direct reads start before offset, holes of skipped bytes must not be counted
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--sparse")
+        .arg("--io-mode")
+        .arg("direct")
+        .arg("--offset")
+        .arg("100")
+        .arg("--verbose")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file[100..]: 2e3919bbac0bdffd121ecdeb9549d4ae\n")
+        .stderr(predicates::str::starts_with(
+            "file[100..]: read 1048476 bytes (0 allocated) in ",
+        ));
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--sparse")
+        .arg("--offset")
+        .arg("100")
+        .arg("--length")
+        .arg("10")
+        .arg("--verbose")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file[100..110]: a63c90cc3684ad8b0a2176a6a8fe9005\n")
+        .stderr(predicates::str::starts_with(
+            "file[100..110]: read 10 bytes (0 allocated) in ",
+        ));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn retries() -> Result {
+    // reading memory of process at offset 0 always fails with EIO
+    Command::cargo_bin("chksum")?
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--retries")
+        .arg("2")
+        .arg("--retry-delay")
+        .arg("1ms")
+        .arg("--verbose")
+        .arg("/proc/self/mem")
+        .assert()
+        .failure()
+        .code(exitcode::IOERR)
+        .stderr(
+            "/proc/self/mem: input/output error (os error 5), retrying in 0.001s\n/proc/self/mem: input/output error \
+             (os error 5), retrying in 0.002s\n/proc/self/mem: input/output error (os error 5)\n",
+        );
+
+    Ok(())
+}
+
+#[test]
+fn changed_during_read() -> Result {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_binary(&vec![0; 1 << 16])?;
+
+    // file keeps growing while it is read byte by byte
+    let stop = Arc::new(AtomicBool::new(false));
+    let appender = {
+        let stop = Arc::clone(&stop);
+        let mut handle = OpenOptions::new().append(true).open(file.path())?;
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                handle.write_all(b"data").expect("Cannot append to file");
+                thread::sleep(Duration::from_millis(1));
+            }
+        })
+    };
+
+    let assert = Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--buffer-size")
+        .arg("1")
+        .arg("--retry-changed")
+        .arg("1")
+        .arg("--verbose")
+        .arg("file")
+        .assert();
+    stop.store(true, Ordering::Relaxed);
+    appender.join().expect("The appender thread has panicked");
+
+    assert
+        .failure()
+        .code(exitcode::TEMPFAIL)
+        .stdout("")
+        .stderr("file: file changed during read, retrying in 0.000s\nfile: file changed during read\n");
+
+    Ok(())
+}
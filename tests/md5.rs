@@ -92,7 +92,7 @@ fn nonexistent_path() -> Result {
         .arg(nonexistent.path())
         .assert()
         .failure()
-        .code(exitcode::IOERR);
+        .code(exitcode::NOINPUT);
 
     Ok(())
 }
@@ -108,9 +108,9 @@ fn stdin_and_path() -> Result {
         .arg("md5")
         .arg("--stdin")
         .arg(dir.path())
+        .write_stdin("")
         .assert()
-        .failure()
-        .code(exitcode::USAGE);
+        .success();
 
     Ok(())
 }
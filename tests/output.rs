@@ -0,0 +1,261 @@
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use assert_fs::TempDir;
+
+mod common;
+use common::Result;
+
+#[test]
+fn zero() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--zero")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\0");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("-z")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .stderr("nonexistent: no such file or directory (os error 2)\n");
+
+    // diagnostics on stderr stay newline-delimited
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("-z")
+        .arg("--summary")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .stderr(predicates::str::starts_with(
+            "nonexistent: no such file or directory (os error 2)\nsummary: 1 input, 0 succeeded, 1 failed,",
+        ));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_path() -> Result {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child(OsStr::from_bytes(b"file\xFF"));
+    file.write_str("data")?;
+
+    let output = Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg(file.path().file_name().unwrap())
+        .assert()
+        .success()
+        .stdout("\\file\\xff: 8d777f385d3dfec8815d20f7496026dc\n")
+        .get_output()
+        .stdout
+        .clone();
+
+    // printed path can be used to reopen the file
+    let list = output.split(|byte| *byte == b':').next().unwrap().to_vec();
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--files-from")
+        .arg("-")
+        .write_stdin(list)
+        .assert()
+        .success()
+        .stdout("\\file\\xff: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--zero")
+        .arg(file.path().file_name().unwrap())
+        .assert()
+        .success()
+        .stdout(&b"file\xFF: 8d777f385d3dfec8815d20f7496026dc\0"[..]);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn sanitize() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("\x1B[2Jfile");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--sanitize")
+        .arg("always")
+        .arg("\x1B[2Jfile")
+        .assert()
+        .success()
+        .stdout("\\\\x1b[2Jfile: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--sanitize")
+        .arg("always")
+        .arg("\x1B[2Jnonexistent")
+        .assert()
+        .failure()
+        .stderr("\\x1b[2Jnonexistent: no such file or directory (os error 2)\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--sanitize")
+        .arg("never")
+        .arg("\x1B[2Jfile")
+        .assert()
+        .success()
+        .stdout("\x1B[2Jfile: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Ok(())
+}
+
+#[test]
+fn quiet() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--quiet")
+        .arg("file")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stdout("")
+        .stderr("nonexistent: no such file or directory (os error 2)\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--status")
+        .arg("file")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stdout("")
+        .stderr("");
+
+    Ok(())
+}
+
+#[test]
+fn verbose_walk() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("dir/file");
+    file.write_str("data")?;
+
+    let file = tmpdir.child("dir/large");
+    file.write_str("datadata")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("-vv")
+        .arg("--max-size")
+        .arg("4")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n")
+        .stderr(predicates::str::starts_with(
+            "dir: reading directory dir with 2 entries\ndir: skipping dir/large: filtered out\ndir: read 4 bytes in ",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn summary() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--summary")
+        .arg("file")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\n")
+        .stderr(predicates::str::starts_with(
+            "nonexistent: no such file or directory (os error 2)\nsummary: 2 inputs, 1 succeeded, 1 failed, 4 bytes \
+             in ",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn progress() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("data")?;
+
+    // progress is not drawn when stderr is not a terminal
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--progress")
+        .arg("--prescan")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout("file: 8d777f385d3dfec8815d20f7496026dc\n")
+        .stderr("");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--prescan")
+        .arg("file")
+        .assert()
+        .failure()
+        .code(exitcode::USAGE);
+
+    Ok(())
+}
@@ -77,7 +77,7 @@ fn nonexistent_path() -> Result {
         .arg(nonexistent.path())
         .assert()
         .failure()
-        .code(exitcode::IOERR);
+        .code(exitcode::NOINPUT);
 
     Ok(())
 }
@@ -93,9 +93,9 @@ fn stdin_and_path() -> Result {
         .arg("sha1")
         .arg("--stdin")
         .arg(dir.path())
+        .write_stdin("")
         .assert()
-        .failure()
-        .code(exitcode::USAGE);
+        .success();
 
     Ok(())
 }
@@ -89,7 +89,7 @@ fn nonexistent_path() -> Result {
         .arg(nonexistent.path())
         .assert()
         .failure()
-        .code(exitcode::IOERR);
+        .code(exitcode::NOINPUT);
 
     Ok(())
 }
@@ -105,9 +105,9 @@ fn stdin_and_path() -> Result {
         .arg("sha2-256")
         .arg("--stdin")
         .arg(dir.path())
+        .write_stdin("")
         .assert()
-        .failure()
-        .code(exitcode::USAGE);
+        .success();
 
     Ok(())
 }
@@ -0,0 +1,221 @@
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use assert_fs::TempDir;
+
+mod common;
+use common::Result;
+
+#[cfg(unix)]
+#[test]
+fn special_files() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("Dir/file");
+    file.write_str("data")?;
+
+    let fifo = tmpdir.child("Dir/FIFO");
+    std::process::Command::new("mkfifo").arg(fifo.path()).status()?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("Dir")
+        .assert()
+        .success()
+        .stdout("Dir: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--special-files")
+        .arg("skip")
+        .arg("Dir")
+        .assert()
+        .success()
+        .stdout("Dir: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--special-files")
+        .arg("error")
+        .arg("Dir")
+        .assert()
+        .failure()
+        .code(exitcode::OSFILE)
+        .stderr("Dir: Dir/FIFO: open: special file\n");
+
+    // rejection by policy is not an unreadable entry
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--special-files")
+        .arg("error")
+        .arg("--skip-unreadable")
+        .arg("Dir")
+        .assert()
+        .failure()
+        .code(exitcode::OSFILE)
+        .stderr("Dir: Dir/FIFO: open: special file\n");
+
+    Ok(())
+}
+
+#[test]
+fn filters() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("dir/small");
+    file.write_str("data")?;
+
+    let file = tmpdir.child("dir/large");
+    file.write_str("datadata")?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--min-size")
+        .arg("5")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: 511ae0b1c13f95e5f08f1a0dd3da3d93\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--max-size")
+        .arg("4")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--newer")
+        .arg("@1")
+        .arg("--max-size")
+        .arg("4")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--older")
+        .arg("1970-01-02")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: d41d8cd98f00b204e9800998ecf8427e\n");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn type_filter() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("file");
+    file.write_str("datadata")?;
+
+    let file = tmpdir.child("dir/file");
+    file.write_str("data")?;
+
+    std::os::unix::fs::symlink(tmpdir.child("file").path(), tmpdir.child("dir/link").path())?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--type")
+        .arg("f")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n");
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--type")
+        .arg("l")
+        .arg("dir")
+        .assert()
+        .success()
+        .stdout("dir: 511ae0b1c13f95e5f08f1a0dd3da3d93\n");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn inner_error() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("dir/File");
+    file.write_str("data")?;
+
+    std::os::unix::fs::symlink("Missing", tmpdir.child("dir/Link").path())?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("dir")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT)
+        .stderr("dir: dir/Link: stat: no such file or directory (os error 2)\n");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn skip_unreadable() -> Result {
+    let tmpdir = TempDir::new()?;
+
+    let file = tmpdir.child("dir/file");
+    file.write_str("data")?;
+
+    std::os::unix::fs::symlink("missing", tmpdir.child("dir/link").path())?;
+
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--color")
+        .arg("never")
+        .arg("--skip-unreadable")
+        .arg("dir")
+        .assert()
+        .failure()
+        .code(1)
+        .stdout("dir: 8d777f385d3dfec8815d20f7496026dc\n")
+        .stderr(
+            "dir: skipped dir/link: stat: no such file or directory (os error 2)\ndir: digest is partial, 1 entry \
+             skipped\n",
+        );
+
+    // partial digest doesn't hide failure of later input
+    Command::cargo_bin("chksum")?
+        .current_dir(tmpdir.path())
+        .arg("md5")
+        .arg("--skip-unreadable")
+        .arg("dir")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .code(exitcode::NOINPUT);
+
+    Ok(())
+}